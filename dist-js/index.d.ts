@@ -11,6 +11,192 @@ export interface QueryResult {
      */
     lastInsertId: number;
 }
+/** The metadata of a column returned by `Database.selectColumns`. */
+export interface ColumnInfo {
+    name: string;
+    /** The name of the column's type, as reported by the database driver. */
+    type_name: string;
+    /**
+     * Whether the column may contain `NULL`, or `null` if the driver does
+     * not know, e.g. for computed columns.
+     */
+    nullable: boolean | null;
+}
+/**
+ * The result of `Database.selectColumns`: the rows are arrays of values
+ * in the order of `columns`.
+ */
+export interface ColumnarRows<T extends unknown[] = unknown[]> {
+    columns: ColumnInfo[];
+    rows: T[];
+}
+/**
+ * The state of a migration registered for a database.
+ */
+export interface MigrationStatus {
+    version: number;
+    description: string;
+    /** When the migration was applied, or `null` if it is pending. */
+    appliedAt: string | null;
+    /**
+     * Whether the applied migration matches the SQL registered in the app.
+     * Always `false` for pending migrations.
+     */
+    checksumMatches: boolean;
+    pending: boolean;
+}
+/**
+ * How integers outside of JavaScript's safe integer range and decimals
+ * (`NUMERIC` / `DECIMAL`) are returned:
+ *
+ * - `"number"` (default): as numbers, which may lose precision.
+ * - `"string"`: as strings holding the exact value. Integers within the
+ *   safe range are still returned as numbers.
+ */
+export type BigNumbers = "number" | "string";
+/**
+ * How binary values (`BLOB`, `BYTEA`, ...) are returned by the select
+ * functions: as arrays of byte values, or as base64 strings which are
+ * much smaller and faster to transfer, see `fromBase64`.
+ */
+export type Binary = "array" | "base64";
+/**
+ * Settings applied to every connection of a SQLite database. Other
+ * pragmas can only be set in the plugin config.
+ */
+export interface SqliteOptions {
+    /**
+     * The directory the path of the database is relative to, the deprecated
+     * app directory of Tauri by default.
+     */
+    baseDir?: "app" | "appData" | "appLocalData" | "appCache";
+    journalMode?: "delete" | "truncate" | "persist" | "memory" | "wal" | "off";
+    /** Seconds to wait for a locked database before failing. */
+    busyTimeout?: number;
+    foreignKeys?: boolean;
+    synchronous?: "off" | "normal" | "full" | "extra";
+    /**
+     * The SQLCipher key of the database, requires the `sqlcipher` feature.
+     * Keys set from Rust take precedence.
+     */
+    key?: string;
+}
+/**
+ * Options of the connection pool of a database. Options left unset are
+ * taken from the plugin config, then from the defaults of sqlx.
+ */
+export interface ConnectionOptions {
+    maxConnections?: number;
+    minConnections?: number;
+    /** Seconds to wait for a connection before giving up. */
+    acquireTimeout?: number;
+    /** Seconds after which an idle connection is closed. */
+    idleTimeout?: number;
+    /** Whether to check that a connection is alive before using it. */
+    testBeforeAcquire?: boolean;
+    /**
+     * Opens the database read-only and rejects `execute` and `begin` calls.
+     * It cannot be turned off when set in the plugin config. With Postgres
+     * and MySQL, queries run in read-only transactions, see the README.
+     */
+    readOnly?: boolean;
+    sqlite?: SqliteOptions;
+}
+export interface LoadOptions extends ConnectionOptions {
+    /** How big integers and decimals are returned by the select functions. */
+    bigNumbers?: BigNumbers;
+    /** How binary values are returned by the select functions. */
+    binary?: Binary;
+}
+/**
+ * A type a bind value can be bound as:
+ *
+ * - `bigint` and `decimal`: exact numbers given as strings
+ * - `integer`, `float`, `boolean` and `text`
+ * - `blob`: an array of bytes, bound as `BLOB` / `BYTEA`
+ * - `json`: any value, bound as JSON even if it is a string or an array
+ * - `date`, `time` and `datetime`: ISO 8601 strings without a time zone
+ * - `timestamptz`: an RFC 3339 string, e.g. `2024-01-31T13:45:00Z`
+ */
+export type ValueType = "bigint" | "decimal" | "integer" | "float" | "boolean" | "text" | "blob" | "json" | "date" | "time" | "datetime" | "timestamptz";
+/**
+ * A bind value tagged with the type it should be bound as, see `typed`.
+ */
+export interface TypedValue {
+    type: ValueType;
+    value: unknown;
+}
+/**
+ * Binds a value as the given type instead of the type matching its
+ * JavaScript value.
+ *
+ * @example
+ * ```ts
+ * await db.select("SELECT * FROM events WHERE day = $1", [
+ *   typed("date", "2024-01-31"),
+ * ]);
+ * ```
+ */
+export declare function typed(type: ValueType, value: unknown): TypedValue;
+/**
+ * Binds bytes as a `BLOB` / `BYTEA`. `Uint8Array` and `ArrayBuffer` bind
+ * values are bound this way without calling it.
+ */
+export declare function blob(bytes: Uint8Array | ArrayBuffer | number[]): TypedValue;
+/**
+ * Decodes a base64 string, as returned for binary values with the
+ * `binary: "base64"` option and by `readBlob`.
+ */
+export declare function fromBase64(encoded: string): Uint8Array;
+/**
+ * Binds a 64-bit integer exactly, including values outside of
+ * JavaScript's safe integer range.
+ *
+ * @example
+ * ```ts
+ * await db.execute("INSERT INTO counters (value) VALUES ($1)", [
+ *   bigint("9007199254740993"),
+ * ]);
+ * ```
+ */
+export declare function bigint(value: string | bigint | number): TypedValue;
+/**
+ * Binds a decimal exactly, for `NUMERIC` / `DECIMAL` columns.
+ *
+ * @example
+ * ```ts
+ * await db.execute("INSERT INTO prices (amount) VALUES ($1)", [
+ *   decimal("19.99"),
+ * ]);
+ * ```
+ */
+export declare function decimal(value: string): TypedValue;
+/**
+ * The values bound to a query: an array for positional placeholders, or
+ * an object for `:name` / `@name` placeholders, which work the same with
+ * every driver.
+ *
+ * @example
+ * ```ts
+ * await db.select("SELECT * FROM todos WHERE status = :status", {
+ *   status: "done",
+ * });
+ * ```
+ */
+export type BindValues = unknown[] | Record<string, unknown>;
+/**
+ * A statement of a batch passed to `Database.executeBatch`.
+ */
+export interface BatchStatement {
+    /** The SQL statement to run. */
+    query: string;
+    /**
+     * The sets of bind values; the statement runs once for each set, or
+     * once without bind values if `values` is not given. An empty list
+     * runs nothing.
+     */
+    values?: BindValues[];
+}
 /**
  * **Database**
  *
@@ -19,7 +205,8 @@ export interface QueryResult {
  */
 export default class Database {
     path: string;
-    constructor(path: string);
+    options: LoadOptions;
+    constructor(path: string, options?: LoadOptions);
     /**
      * **load**
      *
@@ -28,14 +215,29 @@ export default class Database {
      *
      * # Sqlite
      *
-     * The path is relative to `tauri::api::path::BaseDirectory::App` and must start with `sqlite:`.
+     * The path is relative to `tauri::api::path::BaseDirectory::App`, or the
+     * directory set with the `sqlite.baseDir` option, and must start with `sqlite:`.
+     * In-memory databases are loaded with `sqlite::memory:`, or with a URI like
+     * `file:name?mode=memory&cache=shared` for a named one, and live until
+     * they are closed.
      *
      * @example
      * ```ts
      * const db = await Database.load("sqlite:test.db");
+     *
+     * // return big integers and decimals as exact strings
+     * const db = await Database.load("postgres://localhost/test", {
+     *   bigNumbers: "string",
+     * });
+     *
+     * // configure the connection pool
+     * const db = await Database.load("postgres://localhost/test", {
+     *   maxConnections: 5,
+     *   acquireTimeout: 10,
+     * });
      * ```
      */
-    static load(path: string): Promise<Database>;
+    static load(path: string, options?: LoadOptions): Promise<Database>;
     /**
      * **get**
      *
@@ -52,7 +254,7 @@ export default class Database {
      * const db = Database.get("sqlite:test.db");
      * ```
      */
-    static get(path: string): Database;
+    static get(path: string, options?: LoadOptions): Database;
     /**
      * **execute**
      *
@@ -85,7 +287,32 @@ export default class Database {
      * );
      * ```
      */
-    execute(query: string, bindValues?: unknown[]): Promise<QueryResult>;
+    execute(query: string, bindValues?: BindValues): Promise<QueryResult>;
+    /**
+     * **executeBatch**
+     *
+     * Runs a batch of statements in a single round trip, inside a single
+     * transaction: either every statement lands or none does. Returns one
+     * result per executed statement, in order.
+     *
+     * @example
+     * ```ts
+     * // one statement with many sets of bind values
+     * const results = await db.executeBatch([
+     *   {
+     *     query: "INSERT INTO todos (title, status) VALUES ($1, $2)",
+     *     values: todos.map((todo) => [todo.title, todo.status]),
+     *   },
+     * ]);
+     *
+     * // distinct statements
+     * await db.executeBatch([
+     *   { query: "DELETE FROM todos WHERE status = $1", values: [["done"]] },
+     *   { query: "UPDATE stats SET cleaned_at = CURRENT_TIMESTAMP" },
+     * ]);
+     * ```
+     */
+    executeBatch(statements: BatchStatement[]): Promise<QueryResult[]>;
     /**
      * **select**
      *
@@ -104,7 +331,145 @@ export default class Database {
      * );
      * ```
      */
-    select<T>(query: string, bindValues?: unknown[]): Promise<T>;
+    select<T>(query: string, bindValues?: BindValues): Promise<T>;
+    /**
+     * **executeNamed**
+     *
+     * Like `execute`, for a query registered in Rust with
+     * `Builder::add_named_query`.
+     *
+     * @example
+     * ```ts
+     * await db.executeNamed("complete_todo", [id]);
+     * ```
+     */
+    executeNamed(name: string, bindValues?: BindValues): Promise<QueryResult>;
+    /**
+     * **selectNamed**
+     *
+     * Like `select`, for a query registered in Rust with
+     * `Builder::add_named_query`.
+     *
+     * @example
+     * ```ts
+     * const todos = await db.selectNamed<Todo[]>("todos_by_status", ["done"]);
+     * ```
+     */
+    selectNamed<T>(name: string, bindValues?: BindValues): Promise<T>;
+    /**
+     * **selectColumns**
+     *
+     * Like `select`, but returns each row as an array of values along with
+     * the name, type and nullability of every column. Unlike `select`, this
+     * keeps the order of the columns and columns sharing the same name, such
+     * as the `id` columns of a join.
+     *
+     * @example
+     * ```ts
+     * const { columns, rows } = await db.selectColumns(
+     *   "SELECT * FROM todos JOIN users ON users.id = todos.user_id",
+     * );
+     * ```
+     */
+    selectColumns<T extends unknown[] = unknown[]>(query: string, bindValues?: BindValues): Promise<ColumnarRows<T>>;
+    /**
+     * **readBlob**
+     *
+     * Reads a single binary value, the first column of the first row of the
+     * query, without the overhead of converting a row. Returns `null` if
+     * there is no row or the value is `NULL`.
+     *
+     * @example
+     * ```ts
+     * const image = await db.readBlob(
+     *   "SELECT data FROM images WHERE id = $1",
+     *   [id],
+     * );
+     * ```
+     */
+    readBlob(query: string, bindValues?: BindValues): Promise<Uint8Array | null>;
+    /**
+     * **writeBlob**
+     *
+     * Writes a single binary value, bound to the first placeholder of the
+     * statement followed by `bindValues`, or to `:data` if they are named.
+     *
+     * @example
+     * ```ts
+     * await db.writeBlob(
+     *   "UPDATE images SET data = $1 WHERE id = $2",
+     *   bytes,
+     *   [id],
+     * );
+     * ```
+     */
+    writeBlob(query: string, data: Uint8Array | ArrayBuffer, bindValues?: BindValues): Promise<QueryResult>;
+    /**
+     * **selectStream**
+     *
+     * Passes in a SELECT query to the database and reads its rows
+     * incrementally, in chunks of `chunkSize` rows (100 by default, at
+     * least 1), instead of loading the whole result set at once. Rows are
+     * only fetched from the database as the returned iterator is consumed;
+     * breaking out of the loop early stops the query.
+     *
+     * @example
+     * ```ts
+     * for await (const rows of db.selectStream<Todo>("SELECT * FROM todos")) {
+     *   render(rows);
+     * }
+     * ```
+     */
+    selectStream<T>(query: string, bindValues?: BindValues, chunkSize?: number): AsyncGenerator<T[], void, undefined>;
+    /**
+     * **migrateTo**
+     *
+     * Migrates the database up or down to the given version. Applied
+     * migrations above the version are reverted with their `Down`
+     * migration, which must be registered alongside the `Up` one.
+     *
+     * @example
+     * ```ts
+     * await db.migrateTo(3);
+     * ```
+     */
+    migrateTo(version: number): Promise<void>;
+    /**
+     * **rollbackMigrations**
+     *
+     * Reverts the last `steps` applied migrations of the database.
+     *
+     * @example
+     * ```ts
+     * // revert the latest migration
+     * await db.rollbackMigrations(1);
+     * ```
+     */
+    rollbackMigrations(steps: number): Promise<void>;
+    /**
+     * **migrationStatus**
+     *
+     * Lists the migrations registered for the database, along with whether
+     * and when they were applied.
+     *
+     * @example
+     * ```ts
+     * const pending = (await db.migrationStatus()).filter((m) => m.pending);
+     * ```
+     */
+    migrationStatus(): Promise<MigrationStatus[]>;
+    /**
+     * **rekey**
+     *
+     * Changes the key of a SQLite database encrypted with SQLCipher, which
+     * requires the `sqlcipher` feature.
+     *
+     * @example
+     * ```ts
+     * await db.rekey(newPassphrase);
+     * ```
+     */
+    rekey(key: string): Promise<void>;
     /**
      * **close**
      *
@@ -117,4 +482,156 @@ export default class Database {
      * @param db - Optionally state the name of a database if you are managing more than one. Otherwise, all database pools will be in scope.
      */
     close(db?: string): Promise<boolean>;
+    /**
+     * **begin**
+     *
+     * Starts a transaction on a dedicated connection of the database.
+     * Queries run through the returned `Transaction` either all land on
+     * `commit` or are all discarded on `rollback`.
+     *
+     * @example
+     * ```ts
+     * const tx = await db.begin();
+     * try {
+     *   await tx.execute("INSERT INTO todos (title) VALUES ($1)", ["one"]);
+     *   await tx.execute("INSERT INTO todos (title) VALUES ($1)", ["two"]);
+     *   await tx.commit();
+     * } catch (e) {
+     *   await tx.rollback();
+     *   throw e;
+     * }
+     * ```
+     */
+    begin(): Promise<Transaction>;
+    /**
+     * **openBlob**
+     *
+     * Opens the value of `column` in the row `rowId` of `table` to read and
+     * write byte ranges of it without loading the whole value, SQLite only.
+     * The blob holds on to one connection of the pool until it is closed.
+     *
+     * Its size cannot change: to write a new value, first set the column to
+     * `zeroblob(size)` and then write the bytes in chunks.
+     *
+     * @example
+     * ```ts
+     * const blob = await db.openBlob("attachments", "data", rowId);
+     * try {
+     *   const header = await blob.read(0, 16);
+     * } finally {
+     *   await blob.close();
+     * }
+     * ```
+     */
+    openBlob(table: string, column: string, rowId: number, options?: {
+        readOnly?: boolean;
+    }): Promise<BlobHandle>;
+}
+/**
+ * **BlobHandle**
+ *
+ * A value opened with `Database.openBlob`. It must be closed with
+ * `close`, otherwise its connection stays checked out of the pool until
+ * the database is closed.
+ */
+export declare class BlobHandle {
+    id: number;
+    /** The size of the value in bytes. */
+    size: number;
+    constructor(id: number, size: number);
+    /**
+     * **read**
+     *
+     * Reads `length` bytes from `offset`, or up to the end of the value.
+     */
+    read(offset?: number, length?: number): Promise<Uint8Array>;
+    /**
+     * **write**
+     *
+     * Writes bytes from `offset`, which must fit in the size of the value.
+     */
+    write(data: Uint8Array | ArrayBuffer, offset?: number): Promise<void>;
+    /**
+     * **close**
+     *
+     * Closes the blob and returns its connection to the pool.
+     */
+    close(): Promise<void>;
+}
+/**
+ * **Transaction**
+ *
+ * A transaction started with `Database.begin`. It must be finished with
+ * either `commit` or `rollback`, otherwise its connection stays checked
+ * out of the pool until the database is closed.
+ */
+export declare class Transaction {
+    path: string;
+    id: number;
+    options: LoadOptions;
+    constructor(path: string, id: number, options?: LoadOptions);
+    /**
+     * **execute**
+     *
+     * Passes a SQL expression to the database for execution as part of
+     * the transaction.
+     */
+    execute(query: string, bindValues?: BindValues): Promise<QueryResult>;
+    /**
+     * **executeBatch**
+     *
+     * Runs a batch of statements in a single round trip as part of the
+     * transaction.
+     */
+    executeBatch(statements: BatchStatement[]): Promise<QueryResult[]>;
+    /**
+     * **select**
+     *
+     * Passes in a SELECT query to the database for execution as part of
+     * the transaction.
+     */
+    select<T>(query: string, bindValues?: BindValues): Promise<T>;
+    /**
+     * **executeNamed**
+     *
+     * Like `execute`, for a named query, as part of the transaction.
+     */
+    executeNamed(name: string, bindValues?: BindValues): Promise<QueryResult>;
+    /**
+     * **selectNamed**
+     *
+     * Like `select`, for a named query, as part of the transaction.
+     */
+    selectNamed<T>(name: string, bindValues?: BindValues): Promise<T>;
+    /**
+     * **selectColumns**
+     *
+     * Like `select`, but returns each row as an array of values along with
+     * the metadata of every column, as part of the transaction.
+     */
+    selectColumns<T extends unknown[] = unknown[]>(query: string, bindValues?: BindValues): Promise<ColumnarRows<T>>;
+    /**
+     * **readBlob**
+     *
+     * Like `Database.readBlob`, as part of the transaction.
+     */
+    readBlob(query: string, bindValues?: BindValues): Promise<Uint8Array | null>;
+    /**
+     * **writeBlob**
+     *
+     * Like `Database.writeBlob`, as part of the transaction.
+     */
+    writeBlob(query: string, data: Uint8Array | ArrayBuffer, bindValues?: BindValues): Promise<QueryResult>;
+    /**
+     * **commit**
+     *
+     * Commits the transaction and returns its connection to the pool.
+     */
+    commit(): Promise<void>;
+    /**
+     * **rollback**
+     *
+     * Rolls the transaction back and returns its connection to the pool.
+     */
+    rollback(): Promise<void>;
 }
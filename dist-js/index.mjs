@@ -1,5 +1,96 @@
 import { invoke } from '@tauri-apps/api/tauri';
 
+/**
+ * Binds a value as the given type instead of the type matching its
+ * JavaScript value.
+ *
+ * @example
+ * ```ts
+ * await db.select("SELECT * FROM events WHERE day = $1", [
+ *   typed("date", "2024-01-31"),
+ * ]);
+ * ```
+ */
+function typed(type, value) {
+    return { type, value };
+}
+/**
+ * Binds bytes as a `BLOB` / `BYTEA`. `Uint8Array` and `ArrayBuffer` bind
+ * values are bound this way without calling it.
+ */
+function blob(bytes) {
+    const array = bytes instanceof ArrayBuffer ? new Uint8Array(bytes) : Uint8Array.from(bytes);
+    return { type: "blob", value: toBase64(array) };
+}
+/** Encodes bytes as base64, which is how they are sent to the backend. */
+function toBase64(bytes) {
+    let binary = "";
+    // in chunks, as spreading too many arguments overflows the stack
+    for (let i = 0; i < bytes.length; i += 0x8000) {
+        binary += String.fromCharCode(...bytes.subarray(i, i + 0x8000));
+    }
+    return btoa(binary);
+}
+/**
+ * Decodes a base64 string, as returned for binary values with the
+ * `binary: "base64"` option and by `readBlob`.
+ */
+function fromBase64(encoded) {
+    const binary = atob(encoded);
+    const bytes = new Uint8Array(binary.length);
+    for (let i = 0; i < binary.length; i++) {
+        bytes[i] = binary.charCodeAt(i);
+    }
+    return bytes;
+}
+function encodeValue(value) {
+    return value instanceof Uint8Array || value instanceof ArrayBuffer
+        ? blob(value)
+        : value;
+}
+/** Prepares bind values to be sent to the backend. */
+function encodeValues(values) {
+    if (values === undefined) {
+        return [];
+    }
+    if (Array.isArray(values)) {
+        return values.map(encodeValue);
+    }
+    return Object.fromEntries(Object.entries(values).map(([name, value]) => [name, encodeValue(value)]));
+}
+function encodeStatements(statements) {
+    return statements.map(({ query, values }) => ({
+        query,
+        values: values === null || values === void 0 ? void 0 : values.map(encodeValues),
+    }));
+}
+/**
+ * Binds a 64-bit integer exactly, including values outside of
+ * JavaScript's safe integer range.
+ *
+ * @example
+ * ```ts
+ * await db.execute("INSERT INTO counters (value) VALUES ($1)", [
+ *   bigint("9007199254740993"),
+ * ]);
+ * ```
+ */
+function bigint(value) {
+    return { type: "bigint", value: value.toString() };
+}
+/**
+ * Binds a decimal exactly, for `NUMERIC` / `DECIMAL` columns.
+ *
+ * @example
+ * ```ts
+ * await db.execute("INSERT INTO prices (amount) VALUES ($1)", [
+ *   decimal("19.99"),
+ * ]);
+ * ```
+ */
+function decimal(value) {
+    return { type: "decimal", value };
+}
 /**
  * **Database**
  *
@@ -7,8 +98,9 @@ import { invoke } from '@tauri-apps/api/tauri';
  * communicating with the rust side of the sql plugin.
  */
 class Database {
-    constructor(path) {
+    constructor(path, options = {}) {
         this.path = path;
+        this.options = options;
     }
     /**
      * **load**
@@ -18,18 +110,34 @@ class Database {
      *
      * # Sqlite
      *
-     * The path is relative to `tauri::api::path::BaseDirectory::App` and must start with `sqlite:`.
+     * The path is relative to `tauri::api::path::BaseDirectory::App`, or the
+     * directory set with the `sqlite.baseDir` option, and must start with `sqlite:`.
+     * In-memory databases are loaded with `sqlite::memory:`, or with a URI like
+     * `file:name?mode=memory&cache=shared` for a named one, and live until
+     * they are closed.
      *
      * @example
      * ```ts
      * const db = await Database.load("sqlite:test.db");
+     *
+     * // return big integers and decimals as exact strings
+     * const db = await Database.load("postgres://localhost/test", {
+     *   bigNumbers: "string",
+     * });
+     *
+     * // configure the connection pool
+     * const db = await Database.load("postgres://localhost/test", {
+     *   maxConnections: 5,
+     *   acquireTimeout: 10,
+     * });
      * ```
      */
-    static async load(path) {
+    static async load(path, options) {
         const _path = await invoke("plugin:sql|load", {
             db: path,
+            options,
         });
-        return new Database(_path);
+        return new Database(_path, options);
     }
     /**
      * **get**
@@ -47,8 +155,8 @@ class Database {
      * const db = Database.get("sqlite:test.db");
      * ```
      */
-    static get(path) {
-        return new Database(path);
+    static get(path, options) {
+        return new Database(path, options);
     }
     /**
      * **execute**
@@ -86,13 +194,47 @@ class Database {
         const [rowsAffected, lastInsertId] = await invoke("plugin:sql|execute", {
             db: this.path,
             query,
-            values: bindValues !== null && bindValues !== void 0 ? bindValues : [],
+            values: encodeValues(bindValues),
         });
         return {
             lastInsertId,
             rowsAffected,
         };
     }
+    /**
+     * **executeBatch**
+     *
+     * Runs a batch of statements in a single round trip, inside a single
+     * transaction: either every statement lands or none does. Returns one
+     * result per executed statement, in order.
+     *
+     * @example
+     * ```ts
+     * // one statement with many sets of bind values
+     * const results = await db.executeBatch([
+     *   {
+     *     query: "INSERT INTO todos (title, status) VALUES ($1, $2)",
+     *     values: todos.map((todo) => [todo.title, todo.status]),
+     *   },
+     * ]);
+     *
+     * // distinct statements
+     * await db.executeBatch([
+     *   { query: "DELETE FROM todos WHERE status = $1", values: [["done"]] },
+     *   { query: "UPDATE stats SET cleaned_at = CURRENT_TIMESTAMP" },
+     * ]);
+     * ```
+     */
+    async executeBatch(statements) {
+        const results = await invoke("plugin:sql|execute_batch", {
+            db: this.path,
+            statements: encodeStatements(statements),
+        });
+        return results.map(([rowsAffected, lastInsertId]) => ({
+            lastInsertId,
+            rowsAffected,
+        }));
+    }
     /**
      * **select**
      *
@@ -115,10 +257,245 @@ class Database {
         const result = await invoke("plugin:sql|select", {
             db: this.path,
             query,
-            values: bindValues !== null && bindValues !== void 0 ? bindValues : [],
+            values: encodeValues(bindValues),
+            bigNumbers: this.options.bigNumbers,
+            binary: this.options.binary,
         });
         return result;
     }
+    /**
+     * **executeNamed**
+     *
+     * Like `execute`, for a query registered in Rust with
+     * `Builder::add_named_query`.
+     *
+     * @example
+     * ```ts
+     * await db.executeNamed("complete_todo", [id]);
+     * ```
+     */
+    async executeNamed(name, bindValues) {
+        const [rowsAffected, lastInsertId] = await invoke("plugin:sql|run_named", {
+            db: this.path,
+            name,
+            values: encodeValues(bindValues),
+            kind: "execute",
+        });
+        return {
+            lastInsertId,
+            rowsAffected,
+        };
+    }
+    /**
+     * **selectNamed**
+     *
+     * Like `select`, for a query registered in Rust with
+     * `Builder::add_named_query`.
+     *
+     * @example
+     * ```ts
+     * const todos = await db.selectNamed("todos_by_status", ["done"]);
+     * ```
+     */
+    async selectNamed(name, bindValues) {
+        return await invoke("plugin:sql|run_named", {
+            db: this.path,
+            name,
+            values: encodeValues(bindValues),
+            kind: "select",
+            bigNumbers: this.options.bigNumbers,
+            binary: this.options.binary,
+        });
+    }
+    /**
+     * **selectColumns**
+     *
+     * Like `select`, but returns each row as an array of values along with
+     * the name, type and nullability of every column. Unlike `select`, this
+     * keeps the order of the columns and columns sharing the same name, such
+     * as the `id` columns of a join.
+     *
+     * @example
+     * ```ts
+     * const { columns, rows } = await db.selectColumns(
+     *   "SELECT * FROM todos JOIN users ON users.id = todos.user_id",
+     * );
+     * ```
+     */
+    async selectColumns(query, bindValues) {
+        return await invoke("plugin:sql|select_columns", {
+            db: this.path,
+            query,
+            values: encodeValues(bindValues),
+            bigNumbers: this.options.bigNumbers,
+            binary: this.options.binary,
+        });
+    }
+    /**
+     * **readBlob**
+     *
+     * Reads a single binary value, the first column of the first row of the
+     * query, without the overhead of converting a row. Returns `null` if
+     * there is no row or the value is `NULL`.
+     *
+     * @example
+     * ```ts
+     * const image = await db.readBlob(
+     *   "SELECT data FROM images WHERE id = $1",
+     *   [id],
+     * );
+     * ```
+     */
+    async readBlob(query, bindValues) {
+        const encoded = await invoke("plugin:sql|read_blob", {
+            db: this.path,
+            query,
+            values: encodeValues(bindValues),
+        });
+        return encoded === null ? null : fromBase64(encoded);
+    }
+    /**
+     * **writeBlob**
+     *
+     * Writes a single binary value, bound to the first placeholder of the
+     * statement followed by `bindValues`, or to `:data` if they are named.
+     *
+     * @example
+     * ```ts
+     * await db.writeBlob(
+     *   "UPDATE images SET data = $1 WHERE id = $2",
+     *   bytes,
+     *   [id],
+     * );
+     * ```
+     */
+    async writeBlob(query, data, bindValues) {
+        const [rowsAffected, lastInsertId] = await invoke("plugin:sql|write_blob", {
+            db: this.path,
+            query,
+            data: blob(data).value,
+            values: encodeValues(bindValues),
+        });
+        return {
+            lastInsertId,
+            rowsAffected,
+        };
+    }
+    /**
+     * **selectStream**
+     *
+     * Passes in a SELECT query to the database and reads its rows
+     * incrementally, in chunks of `chunkSize` rows (100 by default), instead
+     * of loading the whole result set at once. Rows are only fetched from
+     * the database as the returned iterator is consumed; breaking out of the
+     * loop early stops the query.
+     *
+     * @example
+     * ```ts
+     * for await (const rows of db.selectStream("SELECT * FROM todos")) {
+     *   render(rows);
+     * }
+     * ```
+     */
+    async *selectStream(query, bindValues, chunkSize) {
+        const cursorId = await invoke("plugin:sql|select_stream", {
+            db: this.path,
+            query,
+            values: encodeValues(bindValues),
+            chunkSize,
+            bigNumbers: this.options.bigNumbers,
+            binary: this.options.binary,
+        });
+        let done = false;
+        try {
+            while (true) {
+                const rows = await invoke("plugin:sql|fetch_next", {
+                    cursorId,
+                });
+                if (rows === null) {
+                    done = true;
+                    return;
+                }
+                yield rows;
+            }
+        } catch (e) {
+            // the cursor is closed by the backend when fetching fails
+            done = true;
+            throw e;
+        } finally {
+            if (!done) {
+                await invoke("plugin:sql|close_cursor", { cursorId });
+            }
+        }
+    }
+    /**
+     * **migrateTo**
+     *
+     * Migrates the database up or down to the given version. Applied
+     * migrations above the version are reverted with their `Down`
+     * migration, which must be registered alongside the `Up` one.
+     *
+     * @example
+     * ```ts
+     * await db.migrateTo(3);
+     * ```
+     */
+    async migrateTo(version) {
+        await invoke("plugin:sql|migrate_to", {
+            db: this.path,
+            version,
+        });
+    }
+    /**
+     * **rollbackMigrations**
+     *
+     * Reverts the last `steps` applied migrations of the database.
+     *
+     * @example
+     * ```ts
+     * // revert the latest migration
+     * await db.rollbackMigrations(1);
+     * ```
+     */
+    async rollbackMigrations(steps) {
+        await invoke("plugin:sql|rollback_migrations", {
+            db: this.path,
+            steps,
+        });
+    }
+    /**
+     * **migrationStatus**
+     *
+     * Lists the migrations registered for the database, along with whether
+     * and when they were applied.
+     *
+     * @example
+     * ```ts
+     * const pending = (await db.migrationStatus()).filter((m) => m.pending);
+     * ```
+     */
+    async migrationStatus() {
+        return await invoke("plugin:sql|migration_status", {
+            db: this.path,
+        });
+    }
+    /**
+     * **rekey**
+     *
+     * Changes the key of a SQLite database encrypted with SQLCipher, which
+     * requires the `sqlcipher` feature.
+     *
+     * @example
+     * ```ts
+     * await db.rekey(newPassphrase);
+     * ```
+     */
+    async rekey(key) {
+        await invoke("plugin:sql|rekey", {
+            db: this.path,
+            key,
+        });
+    }
     /**
      * **close**
      *
@@ -136,7 +513,279 @@ class Database {
         });
         return success;
     }
+    /**
+     * **begin**
+     *
+     * Starts a transaction on a dedicated connection of the database.
+     * Queries run through the returned `Transaction` either all land on
+     * `commit` or are all discarded on `rollback`.
+     *
+     * @example
+     * ```ts
+     * const tx = await db.begin();
+     * try {
+     *   await tx.execute("INSERT INTO todos (title) VALUES ($1)", ["one"]);
+     *   await tx.execute("INSERT INTO todos (title) VALUES ($1)", ["two"]);
+     *   await tx.commit();
+     * } catch (e) {
+     *   await tx.rollback();
+     *   throw e;
+     * }
+     * ```
+     */
+    async begin() {
+        const id = await invoke("plugin:sql|begin", {
+            db: this.path,
+        });
+        return new Transaction(this.path, id, this.options);
+    }
+    /**
+     * **openBlob**
+     *
+     * Opens the value of `column` in the row `rowId` of `table` to read and
+     * write byte ranges of it without loading the whole value, SQLite only.
+     * The blob holds on to one connection of the pool until it is closed.
+     *
+     * Its size cannot change: to write a new value, first set the column to
+     * `zeroblob(size)` and then write the bytes in chunks.
+     *
+     * @example
+     * ```ts
+     * const blob = await db.openBlob("attachments", "data", rowId);
+     * try {
+     *   const header = await blob.read(0, 16);
+     * } finally {
+     *   await blob.close();
+     * }
+     * ```
+     */
+    async openBlob(table, column, rowId, options = {}) {
+        const { id, size } = await invoke("plugin:sql|open_blob", {
+            db: this.path,
+            table,
+            column,
+            rowId,
+            readOnly: options.readOnly,
+        });
+        return new BlobHandle(id, size);
+    }
+}
+/**
+ * **BlobHandle**
+ *
+ * A value opened with `Database.openBlob`. It must be closed with
+ * `close`, otherwise its connection stays checked out of the pool until
+ * the database is closed.
+ */
+class BlobHandle {
+    constructor(id, size) {
+        this.id = id;
+        this.size = size;
+    }
+    /**
+     * **read**
+     *
+     * Reads `length` bytes from `offset`, or up to the end of the value.
+     */
+    async read(offset = 0, length) {
+        const encoded = await invoke("plugin:sql|read_blob_range", {
+            blobId: this.id,
+            offset,
+            length,
+        });
+        return fromBase64(encoded);
+    }
+    /**
+     * **write**
+     *
+     * Writes bytes from `offset`, which must fit in the size of the value.
+     */
+    async write(data, offset = 0) {
+        await invoke("plugin:sql|write_blob_range", {
+            blobId: this.id,
+            offset,
+            data: blob(data).value,
+        });
+    }
+    /**
+     * **close**
+     *
+     * Closes the blob and returns its connection to the pool.
+     */
+    async close() {
+        await invoke("plugin:sql|close_blob", {
+            blobId: this.id,
+        });
+    }
+}
+/**
+ * **Transaction**
+ *
+ * A transaction started with `Database.begin`. It must be finished with
+ * either `commit` or `rollback`, otherwise its connection stays checked
+ * out of the pool until the database is closed.
+ */
+class Transaction {
+    constructor(path, id, options = {}) {
+        this.path = path;
+        this.id = id;
+        this.options = options;
+    }
+    /**
+     * **execute**
+     *
+     * Passes a SQL expression to the database for execution as part of
+     * the transaction.
+     */
+    async execute(query, bindValues) {
+        const [rowsAffected, lastInsertId] = await invoke("plugin:sql|execute", {
+            db: this.path,
+            query,
+            values: encodeValues(bindValues),
+            transactionId: this.id,
+        });
+        return {
+            lastInsertId,
+            rowsAffected,
+        };
+    }
+    /**
+     * **executeBatch**
+     *
+     * Runs a batch of statements in a single round trip as part of the
+     * transaction.
+     */
+    async executeBatch(statements) {
+        const results = await invoke("plugin:sql|execute_batch", {
+            db: this.path,
+            statements: encodeStatements(statements),
+            transactionId: this.id,
+        });
+        return results.map(([rowsAffected, lastInsertId]) => ({
+            lastInsertId,
+            rowsAffected,
+        }));
+    }
+    /**
+     * **select**
+     *
+     * Passes in a SELECT query to the database for execution as part of
+     * the transaction.
+     */
+    async select(query, bindValues) {
+        const result = await invoke("plugin:sql|select", {
+            db: this.path,
+            query,
+            values: encodeValues(bindValues),
+            transactionId: this.id,
+            bigNumbers: this.options.bigNumbers,
+            binary: this.options.binary,
+        });
+        return result;
+    }
+    /**
+     * **executeNamed**
+     *
+     * Like `execute`, for a named query, as part of the transaction.
+     */
+    async executeNamed(name, bindValues) {
+        const [rowsAffected, lastInsertId] = await invoke("plugin:sql|run_named", {
+            db: this.path,
+            name,
+            values: encodeValues(bindValues),
+            kind: "execute",
+            transactionId: this.id,
+        });
+        return {
+            lastInsertId,
+            rowsAffected,
+        };
+    }
+    /**
+     * **selectNamed**
+     *
+     * Like `select`, for a named query, as part of the transaction.
+     */
+    async selectNamed(name, bindValues) {
+        return await invoke("plugin:sql|run_named", {
+            db: this.path,
+            name,
+            values: encodeValues(bindValues),
+            kind: "select",
+            transactionId: this.id,
+            bigNumbers: this.options.bigNumbers,
+            binary: this.options.binary,
+        });
+    }
+    /**
+     * **selectColumns**
+     *
+     * Like `select`, but returns each row as an array of values along with
+     * the metadata of every column, as part of the transaction.
+     */
+    async selectColumns(query, bindValues) {
+        return await invoke("plugin:sql|select_columns", {
+            db: this.path,
+            query,
+            values: encodeValues(bindValues),
+            transactionId: this.id,
+            bigNumbers: this.options.bigNumbers,
+            binary: this.options.binary,
+        });
+    }
+    /**
+     * **readBlob**
+     *
+     * Like `Database.readBlob`, as part of the transaction.
+     */
+    async readBlob(query, bindValues) {
+        const encoded = await invoke("plugin:sql|read_blob", {
+            db: this.path,
+            query,
+            values: encodeValues(bindValues),
+            transactionId: this.id,
+        });
+        return encoded === null ? null : fromBase64(encoded);
+    }
+    /**
+     * **writeBlob**
+     *
+     * Like `Database.writeBlob`, as part of the transaction.
+     */
+    async writeBlob(query, data, bindValues) {
+        const [rowsAffected, lastInsertId] = await invoke("plugin:sql|write_blob", {
+            db: this.path,
+            query,
+            data: blob(data).value,
+            values: encodeValues(bindValues),
+            transactionId: this.id,
+        });
+        return {
+            lastInsertId,
+            rowsAffected,
+        };
+    }
+    /**
+     * **commit**
+     *
+     * Commits the transaction and returns its connection to the pool.
+     */
+    async commit() {
+        await invoke("plugin:sql|commit", {
+            transactionId: this.id,
+        });
+    }
+    /**
+     * **rollback**
+     *
+     * Rolls the transaction back and returns its connection to the pool.
+     */
+    async rollback() {
+        await invoke("plugin:sql|rollback", {
+            transactionId: this.id,
+        });
+    }
 }
 
-export { Database as default };
+export { BlobHandle, Transaction, bigint, blob, decimal, Database as default, fromBase64, typed };
 //# sourceMappingURL=index.mjs.map
@@ -14,6 +14,274 @@ export interface QueryResult {
   lastInsertId: number;
 }
 
+/** The metadata of a column returned by `Database.selectColumns`. */
+export interface ColumnInfo {
+  name: string;
+  /** The name of the column's type, as reported by the database driver. */
+  type_name: string;
+  /**
+   * Whether the column may contain `NULL`, or `null` if the driver does
+   * not know, e.g. for computed columns.
+   */
+  nullable: boolean | null;
+}
+
+/**
+ * The result of `Database.selectColumns`: the rows are arrays of values
+ * in the order of `columns`.
+ */
+export interface ColumnarRows<T extends unknown[] = unknown[]> {
+  columns: ColumnInfo[];
+  rows: T[];
+}
+
+/**
+ * The state of a migration registered for a database.
+ */
+export interface MigrationStatus {
+  version: number;
+  description: string;
+  /** When the migration was applied, or `null` if it is pending. */
+  appliedAt: string | null;
+  /**
+   * Whether the applied migration matches the SQL registered in the app.
+   * Always `false` for pending migrations.
+   */
+  checksumMatches: boolean;
+  pending: boolean;
+}
+
+/**
+ * How integers outside of JavaScript's safe integer range and decimals
+ * (`NUMERIC` / `DECIMAL`) are returned:
+ *
+ * - `"number"` (default): as numbers, which may lose precision.
+ * - `"string"`: as strings holding the exact value. Integers within the
+ *   safe range are still returned as numbers.
+ */
+export type BigNumbers = "number" | "string";
+
+/**
+ * How binary values (`BLOB`, `BYTEA`, ...) are returned by the select
+ * functions: as arrays of byte values, or as base64 strings which are
+ * much smaller and faster to transfer, see `fromBase64`.
+ */
+export type Binary = "array" | "base64";
+
+/**
+ * Settings applied to every connection of a SQLite database. Other
+ * pragmas can only be set in the plugin config.
+ */
+export interface SqliteOptions {
+  /**
+   * The directory the path of the database is relative to, the deprecated
+   * app directory of Tauri by default.
+   */
+  baseDir?: "app" | "appData" | "appLocalData" | "appCache";
+  journalMode?: "delete" | "truncate" | "persist" | "memory" | "wal" | "off";
+  /** Seconds to wait for a locked database before failing. */
+  busyTimeout?: number;
+  foreignKeys?: boolean;
+  synchronous?: "off" | "normal" | "full" | "extra";
+  /**
+   * The SQLCipher key of the database, requires the `sqlcipher` feature.
+   * Keys set from Rust take precedence.
+   */
+  key?: string;
+}
+
+/**
+ * Options of the connection pool of a database. Options left unset are
+ * taken from the plugin config, then from the defaults of sqlx.
+ */
+export interface ConnectionOptions {
+  maxConnections?: number;
+  minConnections?: number;
+  /** Seconds to wait for a connection before giving up. */
+  acquireTimeout?: number;
+  /** Seconds after which an idle connection is closed. */
+  idleTimeout?: number;
+  /** Whether to check that a connection is alive before using it. */
+  testBeforeAcquire?: boolean;
+  /**
+   * Opens the database read-only and rejects `execute` and `begin` calls.
+   * It cannot be turned off when set in the plugin config. With Postgres
+   * and MySQL, queries run in read-only transactions, see the README.
+   */
+  readOnly?: boolean;
+  sqlite?: SqliteOptions;
+}
+
+export interface LoadOptions extends ConnectionOptions {
+  /** How big integers and decimals are returned by the select functions. */
+  bigNumbers?: BigNumbers;
+  /** How binary values are returned by the select functions. */
+  binary?: Binary;
+}
+
+/**
+ * A type a bind value can be bound as:
+ *
+ * - `bigint` and `decimal`: exact numbers given as strings
+ * - `integer`, `float`, `boolean` and `text`
+ * - `blob`: an array of bytes, bound as `BLOB` / `BYTEA`
+ * - `json`: any value, bound as JSON even if it is a string or an array
+ * - `date`, `time` and `datetime`: ISO 8601 strings without a time zone
+ * - `timestamptz`: an RFC 3339 string, e.g. `2024-01-31T13:45:00Z`
+ */
+export type ValueType =
+  | "bigint"
+  | "decimal"
+  | "integer"
+  | "float"
+  | "boolean"
+  | "text"
+  | "blob"
+  | "json"
+  | "date"
+  | "time"
+  | "datetime"
+  | "timestamptz";
+
+/**
+ * A bind value tagged with the type it should be bound as, see `typed`.
+ */
+export interface TypedValue {
+  type: ValueType;
+  value: unknown;
+}
+
+/**
+ * Binds a value as the given type instead of the type matching its
+ * JavaScript value.
+ *
+ * @example
+ * ```ts
+ * await db.select("SELECT * FROM events WHERE day = $1", [
+ *   typed("date", "2024-01-31"),
+ * ]);
+ * ```
+ */
+export function typed(type: ValueType, value: unknown): TypedValue {
+  return { type, value };
+}
+
+/**
+ * Binds bytes as a `BLOB` / `BYTEA`. `Uint8Array` and `ArrayBuffer` bind
+ * values are bound this way without calling it.
+ */
+export function blob(bytes: Uint8Array | ArrayBuffer | number[]): TypedValue {
+  const array =
+    bytes instanceof ArrayBuffer ? new Uint8Array(bytes) : Uint8Array.from(bytes);
+  return { type: "blob", value: toBase64(array) };
+}
+
+/** Encodes bytes as base64, which is how they are sent to the backend. */
+function toBase64(bytes: Uint8Array): string {
+  let binary = "";
+  // in chunks, as spreading too many arguments overflows the stack
+  for (let i = 0; i < bytes.length; i += 0x8000) {
+    binary += String.fromCharCode(...bytes.subarray(i, i + 0x8000));
+  }
+  return btoa(binary);
+}
+
+/**
+ * Decodes a base64 string, as returned for binary values with the
+ * `binary: "base64"` option and by `readBlob`.
+ */
+export function fromBase64(encoded: string): Uint8Array {
+  const binary = atob(encoded);
+  const bytes = new Uint8Array(binary.length);
+  for (let i = 0; i < binary.length; i++) {
+    bytes[i] = binary.charCodeAt(i);
+  }
+  return bytes;
+}
+
+function encodeValue(value: unknown): unknown {
+  return value instanceof Uint8Array || value instanceof ArrayBuffer
+    ? blob(value)
+    : value;
+}
+
+/** Prepares bind values to be sent to the backend. */
+function encodeValues(values?: BindValues): BindValues {
+  if (values === undefined) {
+    return [];
+  }
+  if (Array.isArray(values)) {
+    return values.map(encodeValue);
+  }
+  return Object.fromEntries(
+    Object.entries(values).map(([name, value]) => [name, encodeValue(value)]),
+  );
+}
+
+function encodeStatements(statements: BatchStatement[]): BatchStatement[] {
+  return statements.map(({ query, values }) => ({
+    query,
+    values: values?.map(encodeValues),
+  }));
+}
+
+/**
+ * Binds a 64-bit integer exactly, including values outside of
+ * JavaScript's safe integer range.
+ *
+ * @example
+ * ```ts
+ * await db.execute("INSERT INTO counters (value) VALUES ($1)", [
+ *   bigint("9007199254740993"),
+ * ]);
+ * ```
+ */
+export function bigint(value: string | bigint | number): TypedValue {
+  return { type: "bigint", value: value.toString() };
+}
+
+/**
+ * Binds a decimal exactly, for `NUMERIC` / `DECIMAL` columns.
+ *
+ * @example
+ * ```ts
+ * await db.execute("INSERT INTO prices (amount) VALUES ($1)", [
+ *   decimal("19.99"),
+ * ]);
+ * ```
+ */
+export function decimal(value: string): TypedValue {
+  return { type: "decimal", value };
+}
+
+/**
+ * The values bound to a query: an array for positional placeholders, or
+ * an object for `:name` / `@name` placeholders, which work the same with
+ * every driver.
+ *
+ * @example
+ * ```ts
+ * await db.select("SELECT * FROM todos WHERE status = :status", {
+ *   status: "done",
+ * });
+ * ```
+ */
+export type BindValues = unknown[] | Record<string, unknown>;
+
+/**
+ * A statement of a batch passed to `Database.executeBatch`.
+ */
+export interface BatchStatement {
+  /** The SQL statement to run. */
+  query: string;
+  /**
+   * The sets of bind values; the statement runs once for each set, or
+   * once without bind values if `values` is not given. An empty list
+   * runs nothing.
+   */
+  values?: BindValues[];
+}
+
 /**
  * **Database**
  *
@@ -22,8 +290,10 @@ export interface QueryResult {
  */
 export default class Database {
   path: string;
-  constructor(path: string) {
+  options: LoadOptions;
+  constructor(path: string, options: LoadOptions = {}) {
     this.path = path;
+    this.options = options;
   }
 
   /**
@@ -34,19 +304,35 @@ export default class Database {
    *
    * # Sqlite
    *
-   * The path is relative to `tauri::api::path::BaseDirectory::App` and must start with `sqlite:`.
+   * The path is relative to `tauri::api::path::BaseDirectory::App`, or the
+   * directory set with the `sqlite.baseDir` option, and must start with `sqlite:`.
+   * In-memory databases are loaded with `sqlite::memory:`, or with a URI like
+   * `file:name?mode=memory&cache=shared` for a named one, and live until
+   * they are closed.
    *
    * @example
    * ```ts
    * const db = await Database.load("sqlite:test.db");
+   *
+   * // return big integers and decimals as exact strings
+   * const db = await Database.load("postgres://localhost/test", {
+   *   bigNumbers: "string",
+   * });
+   *
+   * // configure the connection pool
+   * const db = await Database.load("postgres://localhost/test", {
+   *   maxConnections: 5,
+   *   acquireTimeout: 10,
+   * });
    * ```
    */
-  static async load(path: string): Promise<Database> {
+  static async load(path: string, options?: LoadOptions): Promise<Database> {
     const _path = await invoke<string>("plugin:sql|load", {
       db: path,
+      options,
     });
 
-    return new Database(_path);
+    return new Database(_path, options);
   }
 
   /**
@@ -65,8 +351,8 @@ export default class Database {
    * const db = Database.get("sqlite:test.db");
    * ```
    */
-  static get(path: string): Database {
-    return new Database(path);
+  static get(path: string, options?: LoadOptions): Database {
+    return new Database(path, options);
   }
 
   /**
@@ -101,13 +387,13 @@ export default class Database {
    * );
    * ```
    */
-  async execute(query: string, bindValues?: unknown[]): Promise<QueryResult> {
+  async execute(query: string, bindValues?: BindValues): Promise<QueryResult> {
     const [rowsAffected, lastInsertId] = await invoke<[number, number]>(
       "plugin:sql|execute",
       {
         db: this.path,
         query,
-        values: bindValues ?? [],
+        values: encodeValues(bindValues),
       },
     );
     return {
@@ -115,6 +401,44 @@ export default class Database {
       rowsAffected,
     };
   }
+  /**
+   * **executeBatch**
+   *
+   * Runs a batch of statements in a single round trip, inside a single
+   * transaction: either every statement lands or none does. Returns one
+   * result per executed statement, in order.
+   *
+   * @example
+   * ```ts
+   * // one statement with many sets of bind values
+   * const results = await db.executeBatch([
+   *   {
+   *     query: "INSERT INTO todos (title, status) VALUES ($1, $2)",
+   *     values: todos.map((todo) => [todo.title, todo.status]),
+   *   },
+   * ]);
+   *
+   * // distinct statements
+   * await db.executeBatch([
+   *   { query: "DELETE FROM todos WHERE status = $1", values: [["done"]] },
+   *   { query: "UPDATE stats SET cleaned_at = CURRENT_TIMESTAMP" },
+   * ]);
+   * ```
+   */
+  async executeBatch(statements: BatchStatement[]): Promise<QueryResult[]> {
+    const results = await invoke<Array<[number, number]>>(
+      "plugin:sql|execute_batch",
+      {
+        db: this.path,
+        statements: encodeStatements(statements),
+      },
+    );
+    return results.map(([rowsAffected, lastInsertId]) => ({
+      lastInsertId,
+      rowsAffected,
+    }));
+  }
+
   /**
    * **select**
    *
@@ -133,16 +457,282 @@ export default class Database {
    * );
    * ```
    */
-  async select<T>(query: string, bindValues?: unknown[]): Promise<T> {
+  async select<T>(query: string, bindValues?: BindValues): Promise<T> {
     const result = await invoke<T>("plugin:sql|select", {
       db: this.path,
       query,
-      values: bindValues ?? [],
+      values: encodeValues(bindValues),
+      bigNumbers: this.options.bigNumbers,
+      binary: this.options.binary,
     });
 
     return result;
   }
 
+  /**
+   * **executeNamed**
+   *
+   * Like `execute`, for a query registered in Rust with
+   * `Builder::add_named_query`.
+   *
+   * @example
+   * ```ts
+   * await db.executeNamed("complete_todo", [id]);
+   * ```
+   */
+  async executeNamed(name: string, bindValues?: BindValues): Promise<QueryResult> {
+    const [rowsAffected, lastInsertId] = await invoke<[number, number]>(
+      "plugin:sql|run_named",
+      {
+        db: this.path,
+        name,
+        values: encodeValues(bindValues),
+        kind: "execute",
+      },
+    );
+    return {
+      lastInsertId,
+      rowsAffected,
+    };
+  }
+
+  /**
+   * **selectNamed**
+   *
+   * Like `select`, for a query registered in Rust with
+   * `Builder::add_named_query`.
+   *
+   * @example
+   * ```ts
+   * const todos = await db.selectNamed<Todo[]>("todos_by_status", ["done"]);
+   * ```
+   */
+  async selectNamed<T>(name: string, bindValues?: BindValues): Promise<T> {
+    return await invoke<T>("plugin:sql|run_named", {
+      db: this.path,
+      name,
+      values: encodeValues(bindValues),
+      kind: "select",
+      bigNumbers: this.options.bigNumbers,
+      binary: this.options.binary,
+    });
+  }
+
+  /**
+   * **selectColumns**
+   *
+   * Like `select`, but returns each row as an array of values along with
+   * the name, type and nullability of every column. Unlike `select`, this
+   * keeps the order of the columns and columns sharing the same name, such
+   * as the `id` columns of a join.
+   *
+   * @example
+   * ```ts
+   * const { columns, rows } = await db.selectColumns(
+   *   "SELECT * FROM todos JOIN users ON users.id = todos.user_id",
+   * );
+   * ```
+   */
+  async selectColumns<T extends unknown[] = unknown[]>(
+    query: string,
+    bindValues?: BindValues,
+  ): Promise<ColumnarRows<T>> {
+    return await invoke<ColumnarRows<T>>("plugin:sql|select_columns", {
+      db: this.path,
+      query,
+      values: encodeValues(bindValues),
+      bigNumbers: this.options.bigNumbers,
+      binary: this.options.binary,
+    });
+  }
+
+  /**
+   * **readBlob**
+   *
+   * Reads a single binary value, the first column of the first row of the
+   * query, without the overhead of converting a row. Returns `null` if
+   * there is no row or the value is `NULL`.
+   *
+   * @example
+   * ```ts
+   * const image = await db.readBlob(
+   *   "SELECT data FROM images WHERE id = $1",
+   *   [id],
+   * );
+   * ```
+   */
+  async readBlob(
+    query: string,
+    bindValues?: BindValues,
+  ): Promise<Uint8Array | null> {
+    const encoded = await invoke<string | null>("plugin:sql|read_blob", {
+      db: this.path,
+      query,
+      values: encodeValues(bindValues),
+    });
+    return encoded === null ? null : fromBase64(encoded);
+  }
+
+  /**
+   * **writeBlob**
+   *
+   * Writes a single binary value, bound to the first placeholder of the
+   * statement followed by `bindValues`, or to `:data` if they are named.
+   *
+   * @example
+   * ```ts
+   * await db.writeBlob(
+   *   "UPDATE images SET data = $1 WHERE id = $2",
+   *   bytes,
+   *   [id],
+   * );
+   * ```
+   */
+  async writeBlob(
+    query: string,
+    data: Uint8Array | ArrayBuffer,
+    bindValues?: BindValues,
+  ): Promise<QueryResult> {
+    const [rowsAffected, lastInsertId] = await invoke<[number, number]>(
+      "plugin:sql|write_blob",
+      {
+        db: this.path,
+        query,
+        data: blob(data).value,
+        values: encodeValues(bindValues),
+      },
+    );
+    return {
+      lastInsertId,
+      rowsAffected,
+    };
+  }
+
+  /**
+   * **selectStream**
+   *
+   * Passes in a SELECT query to the database and reads its rows
+   * incrementally, in chunks of `chunkSize` rows (100 by default, at
+   * least 1), instead of loading the whole result set at once. Rows are
+   * only fetched from the database as the returned iterator is consumed;
+   * breaking out of the loop early stops the query.
+   *
+   * @example
+   * ```ts
+   * for await (const rows of db.selectStream<Todo>("SELECT * FROM todos")) {
+   *   render(rows);
+   * }
+   * ```
+   */
+  async *selectStream<T>(
+    query: string,
+    bindValues?: BindValues,
+    chunkSize?: number,
+  ): AsyncGenerator<T[], void, undefined> {
+    const cursorId = await invoke<number>("plugin:sql|select_stream", {
+      db: this.path,
+      query,
+      values: encodeValues(bindValues),
+      chunkSize,
+      bigNumbers: this.options.bigNumbers,
+      binary: this.options.binary,
+    });
+
+    let done = false;
+    try {
+      while (true) {
+        const rows = await invoke<T[] | null>("plugin:sql|fetch_next", {
+          cursorId,
+        });
+        if (rows === null) {
+          done = true;
+          return;
+        }
+        yield rows;
+      }
+    } catch (e) {
+      // the cursor is closed by the backend when fetching fails
+      done = true;
+      throw e;
+    } finally {
+      if (!done) {
+        await invoke("plugin:sql|close_cursor", { cursorId });
+      }
+    }
+  }
+
+  /**
+   * **migrateTo**
+   *
+   * Migrates the database up or down to the given version. Applied
+   * migrations above the version are reverted with their `Down`
+   * migration, which must be registered alongside the `Up` one.
+   *
+   * @example
+   * ```ts
+   * await db.migrateTo(3);
+   * ```
+   */
+  async migrateTo(version: number): Promise<void> {
+    await invoke("plugin:sql|migrate_to", {
+      db: this.path,
+      version,
+    });
+  }
+
+  /**
+   * **rollbackMigrations**
+   *
+   * Reverts the last `steps` applied migrations of the database.
+   *
+   * @example
+   * ```ts
+   * // revert the latest migration
+   * await db.rollbackMigrations(1);
+   * ```
+   */
+  async rollbackMigrations(steps: number): Promise<void> {
+    await invoke("plugin:sql|rollback_migrations", {
+      db: this.path,
+      steps,
+    });
+  }
+
+  /**
+   * **migrationStatus**
+   *
+   * Lists the migrations registered for the database, along with whether
+   * and when they were applied.
+   *
+   * @example
+   * ```ts
+   * const pending = (await db.migrationStatus()).filter((m) => m.pending);
+   * ```
+   */
+  async migrationStatus(): Promise<MigrationStatus[]> {
+    return await invoke<MigrationStatus[]>("plugin:sql|migration_status", {
+      db: this.path,
+    });
+  }
+
+  /**
+   * **rekey**
+   *
+   * Changes the key of a SQLite database encrypted with SQLCipher, which
+   * requires the `sqlcipher` feature.
+   *
+   * @example
+   * ```ts
+   * await db.rekey(newPassphrase);
+   * ```
+   */
+  async rekey(key: string): Promise<void> {
+    await invoke("plugin:sql|rekey", {
+      db: this.path,
+      key,
+    });
+  }
+
   /**
    * **close**
    *
@@ -160,4 +750,330 @@ export default class Database {
     });
     return success;
   }
+
+  /**
+   * **begin**
+   *
+   * Starts a transaction on a dedicated connection of the database.
+   * Queries run through the returned `Transaction` either all land on
+   * `commit` or are all discarded on `rollback`.
+   *
+   * @example
+   * ```ts
+   * const tx = await db.begin();
+   * try {
+   *   await tx.execute("INSERT INTO todos (title) VALUES ($1)", ["one"]);
+   *   await tx.execute("INSERT INTO todos (title) VALUES ($1)", ["two"]);
+   *   await tx.commit();
+   * } catch (e) {
+   *   await tx.rollback();
+   *   throw e;
+   * }
+   * ```
+   */
+  async begin(): Promise<Transaction> {
+    const id = await invoke<number>("plugin:sql|begin", {
+      db: this.path,
+    });
+    return new Transaction(this.path, id, this.options);
+  }
+
+  /**
+   * **openBlob**
+   *
+   * Opens the value of `column` in the row `rowId` of `table` to read and
+   * write byte ranges of it without loading the whole value, SQLite only.
+   * The blob holds on to one connection of the pool until it is closed.
+   *
+   * Its size cannot change: to write a new value, first set the column to
+   * `zeroblob(size)` and then write the bytes in chunks.
+   *
+   * @example
+   * ```ts
+   * const blob = await db.openBlob("attachments", "data", rowId);
+   * try {
+   *   const header = await blob.read(0, 16);
+   * } finally {
+   *   await blob.close();
+   * }
+   * ```
+   */
+  async openBlob(
+    table: string,
+    column: string,
+    rowId: number,
+    options: { readOnly?: boolean } = {},
+  ): Promise<BlobHandle> {
+    const { id, size } = await invoke<{ id: number; size: number }>(
+      "plugin:sql|open_blob",
+      {
+        db: this.path,
+        table,
+        column,
+        rowId,
+        readOnly: options.readOnly,
+      },
+    );
+    return new BlobHandle(id, size);
+  }
+}
+
+/**
+ * **BlobHandle**
+ *
+ * A value opened with `Database.openBlob`. It must be closed with
+ * `close`, otherwise its connection stays checked out of the pool until
+ * the database is closed.
+ */
+export class BlobHandle {
+  id: number;
+  /** The size of the value in bytes. */
+  size: number;
+  constructor(id: number, size: number) {
+    this.id = id;
+    this.size = size;
+  }
+
+  /**
+   * **read**
+   *
+   * Reads `length` bytes from `offset`, or up to the end of the value.
+   */
+  async read(offset = 0, length?: number): Promise<Uint8Array> {
+    const encoded = await invoke<string>("plugin:sql|read_blob_range", {
+      blobId: this.id,
+      offset,
+      length,
+    });
+    return fromBase64(encoded);
+  }
+
+  /**
+   * **write**
+   *
+   * Writes bytes from `offset`, which must fit in the size of the value.
+   */
+  async write(data: Uint8Array | ArrayBuffer, offset = 0): Promise<void> {
+    await invoke("plugin:sql|write_blob_range", {
+      blobId: this.id,
+      offset,
+      data: blob(data).value,
+    });
+  }
+
+  /**
+   * **close**
+   *
+   * Closes the blob and returns its connection to the pool.
+   */
+  async close(): Promise<void> {
+    await invoke("plugin:sql|close_blob", {
+      blobId: this.id,
+    });
+  }
+}
+
+/**
+ * **Transaction**
+ *
+ * A transaction started with `Database.begin`. It must be finished with
+ * either `commit` or `rollback`, otherwise its connection stays checked
+ * out of the pool until the database is closed.
+ */
+export class Transaction {
+  path: string;
+  id: number;
+  options: LoadOptions;
+  constructor(path: string, id: number, options: LoadOptions = {}) {
+    this.path = path;
+    this.id = id;
+    this.options = options;
+  }
+
+  /**
+   * **execute**
+   *
+   * Passes a SQL expression to the database for execution as part of
+   * the transaction.
+   */
+  async execute(query: string, bindValues?: BindValues): Promise<QueryResult> {
+    const [rowsAffected, lastInsertId] = await invoke<[number, number]>(
+      "plugin:sql|execute",
+      {
+        db: this.path,
+        query,
+        values: encodeValues(bindValues),
+        transactionId: this.id,
+      },
+    );
+    return {
+      lastInsertId,
+      rowsAffected,
+    };
+  }
+
+  /**
+   * **executeBatch**
+   *
+   * Runs a batch of statements in a single round trip as part of the
+   * transaction.
+   */
+  async executeBatch(statements: BatchStatement[]): Promise<QueryResult[]> {
+    const results = await invoke<Array<[number, number]>>(
+      "plugin:sql|execute_batch",
+      {
+        db: this.path,
+        statements: encodeStatements(statements),
+        transactionId: this.id,
+      },
+    );
+    return results.map(([rowsAffected, lastInsertId]) => ({
+      lastInsertId,
+      rowsAffected,
+    }));
+  }
+
+  /**
+   * **select**
+   *
+   * Passes in a SELECT query to the database for execution as part of
+   * the transaction.
+   */
+  async select<T>(query: string, bindValues?: BindValues): Promise<T> {
+    const result = await invoke<T>("plugin:sql|select", {
+      db: this.path,
+      query,
+      values: encodeValues(bindValues),
+      transactionId: this.id,
+      bigNumbers: this.options.bigNumbers,
+      binary: this.options.binary,
+    });
+
+    return result;
+  }
+
+  /**
+   * **executeNamed**
+   *
+   * Like `execute`, for a named query, as part of the transaction.
+   */
+  async executeNamed(name: string, bindValues?: BindValues): Promise<QueryResult> {
+    const [rowsAffected, lastInsertId] = await invoke<[number, number]>(
+      "plugin:sql|run_named",
+      {
+        db: this.path,
+        name,
+        values: encodeValues(bindValues),
+        kind: "execute",
+        transactionId: this.id,
+      },
+    );
+    return {
+      lastInsertId,
+      rowsAffected,
+    };
+  }
+
+  /**
+   * **selectNamed**
+   *
+   * Like `select`, for a named query, as part of the transaction.
+   */
+  async selectNamed<T>(name: string, bindValues?: BindValues): Promise<T> {
+    return await invoke<T>("plugin:sql|run_named", {
+      db: this.path,
+      name,
+      values: encodeValues(bindValues),
+      kind: "select",
+      transactionId: this.id,
+      bigNumbers: this.options.bigNumbers,
+      binary: this.options.binary,
+    });
+  }
+
+  /**
+   * **selectColumns**
+   *
+   * Like `select`, but returns each row as an array of values along with
+   * the metadata of every column, as part of the transaction.
+   */
+  async selectColumns<T extends unknown[] = unknown[]>(
+    query: string,
+    bindValues?: BindValues,
+  ): Promise<ColumnarRows<T>> {
+    return await invoke<ColumnarRows<T>>("plugin:sql|select_columns", {
+      db: this.path,
+      query,
+      values: encodeValues(bindValues),
+      transactionId: this.id,
+      bigNumbers: this.options.bigNumbers,
+      binary: this.options.binary,
+    });
+  }
+
+  /**
+   * **readBlob**
+   *
+   * Like `Database.readBlob`, as part of the transaction.
+   */
+  async readBlob(
+    query: string,
+    bindValues?: BindValues,
+  ): Promise<Uint8Array | null> {
+    const encoded = await invoke<string | null>("plugin:sql|read_blob", {
+      db: this.path,
+      query,
+      values: encodeValues(bindValues),
+      transactionId: this.id,
+    });
+    return encoded === null ? null : fromBase64(encoded);
+  }
+
+  /**
+   * **writeBlob**
+   *
+   * Like `Database.writeBlob`, as part of the transaction.
+   */
+  async writeBlob(
+    query: string,
+    data: Uint8Array | ArrayBuffer,
+    bindValues?: BindValues,
+  ): Promise<QueryResult> {
+    const [rowsAffected, lastInsertId] = await invoke<[number, number]>(
+      "plugin:sql|write_blob",
+      {
+        db: this.path,
+        query,
+        data: blob(data).value,
+        values: encodeValues(bindValues),
+        transactionId: this.id,
+      },
+    );
+    return {
+      lastInsertId,
+      rowsAffected,
+    };
+  }
+
+  /**
+   * **commit**
+   *
+   * Commits the transaction and returns its connection to the pool.
+   */
+  async commit(): Promise<void> {
+    await invoke("plugin:sql|commit", {
+      transactionId: this.id,
+    });
+  }
+
+  /**
+   * **rollback**
+   *
+   * Rolls the transaction back and returns its connection to the pool.
+   */
+  async rollback(): Promise<void> {
+    await invoke("plugin:sql|rollback", {
+      transactionId: this.id,
+    });
+  }
 }
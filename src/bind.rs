@@ -0,0 +1,798 @@
+// Copyright 2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::borrow::Cow;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::Deserialize;
+use serde_json::{Map, Value as JsonValue};
+use sqlx::{query::Query, Database, Encode, Type};
+use time::{
+    format_description::well_known::{Iso8601, Rfc3339},
+    Date, OffsetDateTime, PrimitiveDateTime, Time,
+};
+
+#[cfg(feature = "mysql")]
+use sqlx::mysql::MySql;
+#[cfg(feature = "sqlite")]
+use sqlx::sqlite::Sqlite;
+#[cfg(any(feature = "mysql", feature = "postgres"))]
+use sqlx::types::BigDecimal;
+#[cfg(feature = "postgres")]
+use sqlx::{
+    encode::IsNull,
+    error::BoxDynError,
+    postgres::{types::Oid, PgArgumentBuffer, PgTypeInfo, Postgres},
+};
+
+use crate::{Error, Result};
+
+type DbQuery<'q, DB> = Query<'q, DB, <DB as Database>::Arguments<'q>>;
+
+/// Driver specific binding of the values that cannot be bound as plain JSON,
+/// either because JavaScript cannot represent them exactly or because the
+/// driver has a native type for them.
+pub(crate) trait BindExact: Database {
+    /// The text `query` is prepared with when binding `values`, which the
+    /// driver caches its prepared statements by.
+    fn statement<'q>(query: &'q str, _values: &[JsonValue]) -> Cow<'q, str> {
+        Cow::Borrowed(query)
+    }
+
+    /// Binds a string-encoded 64-bit integer.
+    fn bind_bigint<'q>(query: DbQuery<'q, Self>, value: &str) -> Result<DbQuery<'q, Self>>;
+
+    /// Binds a string-encoded decimal, for `NUMERIC` / `DECIMAL` columns.
+    fn bind_decimal<'q>(query: DbQuery<'q, Self>, value: &str) -> Result<DbQuery<'q, Self>>;
+
+    /// Binds a JSON array.
+    fn bind_array<'q>(query: DbQuery<'q, Self>, values: Vec<JsonValue>) -> DbQuery<'q, Self>;
+
+    /// Binds a `NULL` that fits a column of any type.
+    fn bind_null(query: DbQuery<'_, Self>) -> DbQuery<'_, Self>;
+}
+
+#[cfg(feature = "sqlite")]
+impl BindExact for Sqlite {
+    fn bind_bigint<'q>(query: DbQuery<'q, Self>, value: &str) -> Result<DbQuery<'q, Self>> {
+        Ok(query.bind(parse::<i64>("bigint", value)?))
+    }
+
+    fn bind_decimal<'q>(query: DbQuery<'q, Self>, value: &str) -> Result<DbQuery<'q, Self>> {
+        // SQLite has no decimal type, the text is converted according to
+        // the affinity of the column
+        parse::<f64>("decimal", value)?;
+        Ok(query.bind(value.to_string()))
+    }
+
+    fn bind_array<'q>(query: DbQuery<'q, Self>, values: Vec<JsonValue>) -> DbQuery<'q, Self> {
+        query.bind(JsonValue::Array(values))
+    }
+
+    fn bind_null(query: DbQuery<'_, Self>) -> DbQuery<'_, Self> {
+        // the type of a null parameter is not checked against the column
+        query.bind(None::<String>)
+    }
+}
+
+#[cfg(feature = "mysql")]
+impl BindExact for MySql {
+    fn bind_bigint<'q>(query: DbQuery<'q, Self>, value: &str) -> Result<DbQuery<'q, Self>> {
+        // values above `i64::MAX` can only go to `BIGINT UNSIGNED` columns
+        match value.parse::<i64>() {
+            Ok(value) => Ok(query.bind(value)),
+            Err(_) => Ok(query.bind(parse::<u64>("bigint", value)?)),
+        }
+    }
+
+    fn bind_decimal<'q>(query: DbQuery<'q, Self>, value: &str) -> Result<DbQuery<'q, Self>> {
+        Ok(query.bind(parse::<BigDecimal>("decimal", value)?))
+    }
+
+    fn bind_array<'q>(query: DbQuery<'q, Self>, values: Vec<JsonValue>) -> DbQuery<'q, Self> {
+        query.bind(JsonValue::Array(values))
+    }
+
+    fn bind_null(query: DbQuery<'_, Self>) -> DbQuery<'_, Self> {
+        // the type of a null parameter is not checked against the column
+        query.bind(None::<String>)
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl BindExact for Postgres {
+    /// Postgres fixes the parameter types of a statement when preparing it,
+    /// while the types bound here depend on the values, so the types are
+    /// appended to the query as a comment: a statement is then prepared
+    /// once for every combination of types instead of being reused with
+    /// values of the wrong type.
+    fn statement<'q>(query: &'q str, values: &[JsonValue]) -> Cow<'q, str> {
+        if values.is_empty() {
+            return Cow::Borrowed(query);
+        }
+        let types: Vec<&str> = values.iter().map(bound_type).collect();
+        Cow::Owned(format!("{query}\n/* {} */", types.join(", ")))
+    }
+
+    fn bind_bigint<'q>(query: DbQuery<'q, Self>, value: &str) -> Result<DbQuery<'q, Self>> {
+        Ok(query.bind(parse::<i64>("bigint", value)?))
+    }
+
+    fn bind_decimal<'q>(query: DbQuery<'q, Self>, value: &str) -> Result<DbQuery<'q, Self>> {
+        Ok(query.bind(parse::<BigDecimal>("decimal", value)?))
+    }
+
+    fn bind_array<'q>(query: DbQuery<'q, Self>, values: Vec<JsonValue>) -> DbQuery<'q, Self> {
+        match ArrayKind::of(&values) {
+            ArrayKind::Text => query.bind(
+                values
+                    .into_iter()
+                    .map(|v| v.as_str().map(ToOwned::to_owned))
+                    .collect::<Vec<_>>(),
+            ),
+            ArrayKind::Boolean => {
+                query.bind(values.iter().map(JsonValue::as_bool).collect::<Vec<_>>())
+            }
+            ArrayKind::Integer => {
+                query.bind(values.iter().map(JsonValue::as_i64).collect::<Vec<_>>())
+            }
+            ArrayKind::Float => {
+                query.bind(values.iter().map(JsonValue::as_f64).collect::<Vec<_>>())
+            }
+            ArrayKind::Json => query.bind(JsonValue::Array(values)),
+        }
+    }
+
+    fn bind_null(query: DbQuery<'_, Self>) -> DbQuery<'_, Self> {
+        query.bind(UntypedNull)
+    }
+}
+
+/// The type a JSON array is bound as with Postgres, picked from its
+/// elements, e.g. `TEXT[]` for strings. Arrays of other types like `UUID[]`
+/// can be cast in the query, and anything that does not fit an array is
+/// bound as JSON. Empty and all-null arrays are bound as `TEXT[]`, as the
+/// element type of a binary array cannot be left for Postgres to infer.
+#[cfg(feature = "postgres")]
+enum ArrayKind {
+    Text,
+    Boolean,
+    Integer,
+    Float,
+    Json,
+}
+
+#[cfg(feature = "postgres")]
+impl ArrayKind {
+    fn of(values: &[JsonValue]) -> Self {
+        let all = |f: fn(&JsonValue) -> bool| values.iter().all(|v| v.is_null() || f(v));
+        if all(JsonValue::is_string) {
+            Self::Text
+        } else if all(JsonValue::is_boolean) {
+            Self::Boolean
+        } else if all(JsonValue::is_i64) {
+            Self::Integer
+        } else if all(JsonValue::is_number) {
+            Self::Float
+        } else {
+            Self::Json
+        }
+    }
+}
+
+/// The name of the type [`bind_values`] binds a value as with Postgres,
+/// hinted values being named after their hint. Values bound as the same
+/// type may get different names, which only costs another statement.
+#[cfg(feature = "postgres")]
+fn bound_type(value: &JsonValue) -> &'static str {
+    if let Some((type_name, value)) = type_hint(value) {
+        if let Some(type_name) = TYPE_HINTS.iter().find(|hint| **hint == type_name) {
+            return if value.is_null() { "null" } else { type_name };
+        }
+    }
+    match value {
+        JsonValue::Null => "null",
+        JsonValue::Bool(_) => "boolean",
+        JsonValue::Number(value) => match (value.as_i64(), value.as_f64()) {
+            (Some(_), _) => "integer",
+            (None, Some(_)) => "float",
+            (None, None) => "json",
+        },
+        JsonValue::String(_) => "text",
+        JsonValue::Array(values) => match ArrayKind::of(values) {
+            ArrayKind::Text => "text[]",
+            ArrayKind::Boolean => "boolean[]",
+            ArrayKind::Integer => "integer[]",
+            ArrayKind::Float => "float[]",
+            ArrayKind::Json => "json",
+        },
+        JsonValue::Object(_) => "json",
+    }
+}
+
+/// A `NULL` parameter of unspecified type, which Postgres infers from the
+/// query, where `Option::<T>::None` would be declared as a `T` and fail to
+/// e.g. be inserted in a column of another type.
+#[cfg(feature = "postgres")]
+struct UntypedNull;
+
+#[cfg(feature = "postgres")]
+impl Type<Postgres> for UntypedNull {
+    fn type_info() -> PgTypeInfo {
+        PgTypeInfo::with_oid(Oid(0))
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl Encode<'_, Postgres> for UntypedNull {
+    fn encode_by_ref(&self, _: &mut PgArgumentBuffer) -> std::result::Result<IsNull, BoxDynError> {
+        Ok(IsNull::Yes)
+    }
+}
+
+fn parse<T: std::str::FromStr>(type_name: &str, value: &str) -> Result<T> {
+    value
+        .parse()
+        .map_err(|_| Error::InvalidValue(type_name.to_string(), value.to_string()))
+}
+
+/// Reads a `{ "type": "...", "value": ... }` object, used by the frontend
+/// to tell how a value should be bound.
+fn type_hint(value: &JsonValue) -> Option<(&str, &JsonValue)> {
+    let object = value.as_object()?;
+    if object.len() != 2 {
+        return None;
+    }
+    Some((object.get("type")?.as_str()?, object.get("value")?))
+}
+
+fn invalid(type_name: &str, value: &JsonValue) -> Error {
+    let value = match value {
+        JsonValue::String(value) => value.clone(),
+        value => value.to_string(),
+    };
+    Error::InvalidValue(type_name.to_string(), value)
+}
+
+/// The text of a hinted value, numbers and booleans being taken as their
+/// JSON representation.
+fn hinted_text(type_name: &str, value: &JsonValue) -> Result<String> {
+    match value {
+        JsonValue::String(value) => Ok(value.clone()),
+        JsonValue::Number(_) | JsonValue::Bool(_) => Ok(value.to_string()),
+        _ => Err(invalid(type_name, value)),
+    }
+}
+
+/// Parses an ISO 8601 date and/or time, e.g. `2024-01-31`, `13:45:00` or
+/// `2024-01-31T13:45:00.5`. A space is accepted in place of the `T`.
+fn hinted_time<T>(
+    type_name: &str,
+    value: &JsonValue,
+    parse: impl Fn(&str, &Iso8601) -> std::result::Result<T, time::error::Parse>,
+) -> Result<T> {
+    let text = hinted_text(type_name, value)?;
+    parse(&text.replacen(' ', "T", 1), &Iso8601::DEFAULT).map_err(|_| invalid(type_name, value))
+}
+
+/// Reads bytes sent base64 encoded, as the frontend does for `Uint8Array`
+/// values, or as an array of byte values.
+fn hinted_bytes(type_name: &str, value: &JsonValue) -> Result<Vec<u8>> {
+    if let JsonValue::String(encoded) = value {
+        // the error does not repeat the value, which may be large
+        return STANDARD
+            .decode(encoded)
+            .map_err(|e| Error::InvalidValue(type_name.to_string(), e.to_string()));
+    }
+    value
+        .as_array()
+        .and_then(|bytes| {
+            bytes
+                .iter()
+                .map(|byte| byte.as_u64().and_then(|byte| u8::try_from(byte).ok()))
+                .collect()
+        })
+        .ok_or_else(|| invalid(type_name, value))
+}
+
+/// The types a value can be given with a `{ "type": "...", "value": ... }`
+/// object:
+///
+/// - `bigint` and `decimal`: exact numbers given as strings
+/// - `integer`, `float`, `boolean` and `text`
+/// - `blob`: base64 encoded bytes, bound as `BLOB` / `BYTEA`
+/// - `json`: any value, bound as JSON even if it is a string or an array
+/// - `date`, `time` and `datetime`: ISO 8601 strings without a time zone
+/// - `timestamptz`: an RFC 3339 string, e.g. `2024-01-31T13:45:00Z`
+///
+/// A `null` value is bound as `NULL` whatever the type.
+const TYPE_HINTS: [&str; 12] = [
+    "bigint",
+    "decimal",
+    "integer",
+    "float",
+    "boolean",
+    "text",
+    "blob",
+    "json",
+    "date",
+    "time",
+    "datetime",
+    "timestamptz",
+];
+
+/// A value with an explicit type, read from a type hint, see
+/// [`TYPE_HINTS`].
+enum Typed {
+    Null,
+    BigInt(String),
+    Decimal(String),
+    Integer(i64),
+    Float(f64),
+    Boolean(bool),
+    Text(String),
+    Blob(Vec<u8>),
+    Json(JsonValue),
+    Date(Date),
+    Time(Time),
+    DateTime(PrimitiveDateTime),
+    TimestampTz(OffsetDateTime),
+}
+
+impl Typed {
+    /// Reads the value of a type hint, or `None` if the type is unknown, in
+    /// which case the object is bound as JSON like any other.
+    fn from_hint(type_name: &str, value: &JsonValue) -> Option<Result<Self>> {
+        let text = || hinted_text(type_name, value);
+        let typed = match type_name {
+            _ if value.is_null() && TYPE_HINTS.contains(&type_name) => Ok(Self::Null),
+            "bigint" => text().map(Self::BigInt),
+            "decimal" => text().map(Self::Decimal),
+            "integer" => text().and_then(|text| parse(type_name, &text).map(Self::Integer)),
+            "float" => text().and_then(|text| parse(type_name, &text).map(Self::Float)),
+            "boolean" => text().and_then(|text| parse(type_name, &text).map(Self::Boolean)),
+            "text" => text().map(Self::Text),
+            "blob" => hinted_bytes(type_name, value).map(Self::Blob),
+            "json" => Ok(Self::Json(value.clone())),
+            "date" => hinted_time(type_name, value, Date::parse).map(Self::Date),
+            "time" => hinted_time(type_name, value, Time::parse).map(Self::Time),
+            "datetime" => {
+                hinted_time(type_name, value, PrimitiveDateTime::parse).map(Self::DateTime)
+            }
+            "timestamptz" => text().and_then(|text| {
+                OffsetDateTime::parse(&text, &Rfc3339)
+                    .map(Self::TimestampTz)
+                    .map_err(|_| invalid(type_name, value))
+            }),
+            _ => return None,
+        };
+        Some(typed)
+    }
+}
+
+/// Binds the JSON values sent by the frontend to the query's positional
+/// placeholders.
+///
+/// Values are bound with the type matching their JSON type, e.g. integers
+/// as `BIGINT` and booleans as `BOOLEAN`, while objects are bound as JSON.
+/// Type hints, see [`Typed`], bind their value as the given type instead.
+pub(crate) fn bind_values<'q, DB>(
+    mut query: DbQuery<'q, DB>,
+    values: Vec<JsonValue>,
+) -> Result<DbQuery<'q, DB>>
+where
+    DB: BindExact,
+    JsonValue: Encode<'q, DB> + Type<DB>,
+    String: Encode<'q, DB> + Type<DB>,
+    i64: Encode<'q, DB> + Type<DB>,
+    f64: Encode<'q, DB> + Type<DB>,
+    bool: Encode<'q, DB> + Type<DB>,
+    Vec<u8>: Encode<'q, DB> + Type<DB>,
+    Date: Encode<'q, DB> + Type<DB>,
+    Time: Encode<'q, DB> + Type<DB>,
+    PrimitiveDateTime: Encode<'q, DB> + Type<DB>,
+    OffsetDateTime: Encode<'q, DB> + Type<DB>,
+{
+    for value in values {
+        let typed = type_hint(&value)
+            .and_then(|(type_name, value)| Typed::from_hint(type_name, value))
+            .transpose()?;
+        query = match typed {
+            Some(Typed::Null) => DB::bind_null(query),
+            Some(Typed::BigInt(value)) => DB::bind_bigint(query, &value)?,
+            Some(Typed::Decimal(value)) => DB::bind_decimal(query, &value)?,
+            Some(Typed::Integer(value)) => query.bind(value),
+            Some(Typed::Float(value)) => query.bind(value),
+            Some(Typed::Boolean(value)) => query.bind(value),
+            Some(Typed::Text(value)) => query.bind(value),
+            Some(Typed::Blob(value)) => query.bind(value),
+            Some(Typed::Json(value)) => query.bind(value),
+            Some(Typed::Date(value)) => query.bind(value),
+            Some(Typed::Time(value)) => query.bind(value),
+            Some(Typed::DateTime(value)) => query.bind(value),
+            Some(Typed::TimestampTz(value)) => query.bind(value),
+            None => match value {
+                JsonValue::Null => DB::bind_null(query),
+                JsonValue::Bool(value) => query.bind(value),
+                JsonValue::Number(value) => match (value.as_i64(), value.as_f64()) {
+                    (Some(value), _) => query.bind(value),
+                    (None, Some(value)) => query.bind(value),
+                    (None, None) => query.bind(JsonValue::Number(value)),
+                },
+                JsonValue::String(value) => query.bind(value),
+                JsonValue::Array(values) => DB::bind_array(query, values),
+                value @ JsonValue::Object(_) => query.bind(value),
+            },
+        };
+    }
+    Ok(query)
+}
+
+/// The bind values of a query, either positional or by name.
+///
+/// Named values are referenced in the query as `:name` or `@name`, which
+/// [`BindValues::into_positional`] rewrites to the placeholders of the
+/// driver, so that the same query text works with every driver.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub(crate) enum BindValues {
+    Positional(Vec<JsonValue>),
+    Named(Map<String, JsonValue>),
+}
+
+impl Default for BindValues {
+    fn default() -> Self {
+        Self::Positional(Vec::new())
+    }
+}
+
+/// The positional placeholder syntax of a driver.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Placeholders {
+    /// `?`, every placeholder taking the next value.
+    #[cfg(feature = "mysql")]
+    Question,
+    /// `$1`, `$2`, ..., which lets a value be referenced more than once.
+    #[cfg(any(feature = "sqlite", feature = "postgres"))]
+    Numbered,
+}
+
+impl BindValues {
+    /// Returns the query and values to bind to its positional placeholders.
+    ///
+    /// Named placeholders are only rewritten outside of string literals,
+    /// quoted identifiers and comments, and Postgres' `::` casts are never
+    /// taken for a placeholder. A `:name` without a value is an error, while
+    /// an `@name` without one is kept, so that MySQL's `@variables` keep
+    /// working.
+    pub(crate) fn into_positional(
+        self,
+        query: &str,
+        placeholders: Placeholders,
+    ) -> Result<(Cow<'_, str>, Vec<JsonValue>)> {
+        let named = match self {
+            Self::Positional(values) => return Ok((Cow::Borrowed(query), values)),
+            Self::Named(named) => named,
+        };
+
+        let bytes = query.as_bytes();
+        let mut rewritten = String::with_capacity(query.len());
+        let mut values = Vec::new();
+        #[cfg(any(feature = "sqlite", feature = "postgres"))]
+        let mut names: Vec<&str> = Vec::new();
+        let mut copied = 0;
+        let mut i = 0;
+        while i < bytes.len() {
+            match bytes[i] {
+                quote @ (b'\'' | b'"') if placeholders.has_backslash_escapes() => {
+                    i = skip_quoted(bytes, i + 1, quote, true)
+                }
+                // Postgres' escape strings, `E'...'`
+                b'\''
+                    if i > 0
+                        && bytes[i - 1].eq_ignore_ascii_case(&b'e')
+                        && (i == 1 || !is_identifier(bytes[i - 2])) =>
+                {
+                    i = skip_quoted(bytes, i + 1, b'\'', true)
+                }
+                quote @ (b'\'' | b'"' | b'`') => i = skip_quoted(bytes, i + 1, quote, false),
+                b'-' if bytes.get(i + 1) == Some(&b'-') => i = skip_past(bytes, i + 2, b"\n"),
+                b'/' if bytes.get(i + 1) == Some(&b'*') => i = skip_past(bytes, i + 2, b"*/"),
+                b'$' => i = skip_dollar_quoted(bytes, i),
+                b':' if bytes.get(i + 1) == Some(&b':') => i += 2,
+                b':' | b'@' if is_placeholder_start(bytes, i) => {
+                    let end = (i + 1..bytes.len())
+                        .find(|&j| !is_identifier(bytes[j]))
+                        .unwrap_or(bytes.len());
+                    let name = &query[i + 1..end];
+                    if bytes[i] == b':' && !named.contains_key(name) {
+                        return Err(Error::MissingNamedValue(name.to_string()));
+                    }
+                    if let Some(value) = named.get(name) {
+                        rewritten.push_str(&query[copied..i]);
+                        match placeholders {
+                            #[cfg(feature = "mysql")]
+                            Placeholders::Question => {
+                                rewritten.push('?');
+                                values.push(value.clone());
+                            }
+                            #[cfg(any(feature = "sqlite", feature = "postgres"))]
+                            Placeholders::Numbered => {
+                                let index = match names.iter().position(|n| *n == name) {
+                                    Some(index) => index,
+                                    None => {
+                                        names.push(name);
+                                        values.push(value.clone());
+                                        names.len() - 1
+                                    }
+                                };
+                                rewritten.push_str(&format!("${}", index + 1));
+                            }
+                        }
+                        copied = end;
+                    }
+                    i = end;
+                }
+                _ => i += 1,
+            }
+        }
+        rewritten.push_str(&query[copied..]);
+        Ok((Cow::Owned(rewritten), values))
+    }
+}
+
+impl Placeholders {
+    /// Whether a backslash escapes the next character of string literals,
+    /// as it does in MySQL unless `NO_BACKSLASH_ESCAPES` is set.
+    fn has_backslash_escapes(self) -> bool {
+        match self {
+            #[cfg(feature = "mysql")]
+            Self::Question => true,
+            #[cfg(any(feature = "sqlite", feature = "postgres"))]
+            Self::Numbered => false,
+        }
+    }
+}
+
+fn is_identifier(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'_'
+}
+
+/// Whether the `:` or `@` at `i` starts a named placeholder, rather than
+/// e.g. MySQL's `@@system_variables` or a slice like `array[1:2]`.
+fn is_placeholder_start(bytes: &[u8], i: usize) -> bool {
+    let follows_name = i > 0 && (is_identifier(bytes[i - 1]) || bytes[i - 1] == b'@');
+    let starts_name = bytes
+        .get(i + 1)
+        .is_some_and(|&b| b.is_ascii_alphabetic() || b == b'_');
+    !follows_name && starts_name
+}
+
+/// Returns the index right after the literal quoted by `quote` that starts
+/// at `from`, or the end of the query when it is not closed. A doubled
+/// quote is read as two literals, which comes down to the same.
+fn skip_quoted(bytes: &[u8], from: usize, quote: u8, backslash_escapes: bool) -> usize {
+    let mut i = from;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' if backslash_escapes => i += 2,
+            b if b == quote => return i + 1,
+            _ => i += 1,
+        }
+    }
+    bytes.len()
+}
+
+/// Returns the index right after the first `end` found from `from`, or the
+/// end of the query when it is not closed.
+fn skip_past(bytes: &[u8], from: usize, end: &[u8]) -> usize {
+    bytes[from.min(bytes.len())..]
+        .windows(end.len())
+        .position(|window| window == end)
+        .map_or(bytes.len(), |position| from + position + end.len())
+}
+
+/// Skips a Postgres dollar-quoted string like `$$...$$` or `$tag$...$tag$`,
+/// or just the `$` at `i` when it does not start one.
+fn skip_dollar_quoted(bytes: &[u8], i: usize) -> usize {
+    let tag_end = (i + 1..bytes.len()).find(|&j| !is_identifier(bytes[j]));
+    match tag_end {
+        Some(tag_end)
+            if bytes[tag_end] == b'$'
+                && !bytes.get(i + 1).is_some_and(u8::is_ascii_digit)
+                && (i == 0 || !is_identifier(bytes[i - 1])) =>
+        {
+            skip_past(bytes, tag_end + 1, &bytes[i..=tag_end])
+        }
+        _ => i + 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn rewrite(
+        query: &str,
+        values: JsonValue,
+        placeholders: Placeholders,
+    ) -> Result<(String, Vec<JsonValue>)> {
+        let JsonValue::Object(values) = values else {
+            panic!("named values must be an object");
+        };
+        BindValues::Named(values)
+            .into_positional(query, placeholders)
+            .map(|(query, values)| (query.into_owned(), values))
+    }
+
+    #[cfg(any(feature = "sqlite", feature = "postgres"))]
+    fn numbered(query: &str, values: JsonValue) -> Result<(String, Vec<JsonValue>)> {
+        rewrite(query, values, Placeholders::Numbered)
+    }
+
+    #[cfg(feature = "mysql")]
+    fn question(query: &str, values: JsonValue) -> Result<(String, Vec<JsonValue>)> {
+        rewrite(query, values, Placeholders::Question)
+    }
+
+    #[cfg(any(feature = "sqlite", feature = "postgres"))]
+    #[test]
+    fn positional_values_are_kept() {
+        let (query, values) = BindValues::Positional(vec![json!(1)])
+            .into_positional("SELECT :a, $1", Placeholders::Numbered)
+            .unwrap();
+        assert_eq!(query, "SELECT :a, $1");
+        assert_eq!(values, vec![json!(1)]);
+    }
+
+    #[cfg(any(feature = "sqlite", feature = "postgres"))]
+    #[test]
+    fn repeated_names_share_a_number() {
+        let (query, values) = numbered(
+            "SELECT * FROM t WHERE a = :a OR b = @b OR c = :a",
+            json!({ "a": 1, "b": 2 }),
+        )
+        .unwrap();
+        assert_eq!(query, "SELECT * FROM t WHERE a = $1 OR b = $2 OR c = $1");
+        assert_eq!(values, vec![json!(1), json!(2)]);
+    }
+
+    #[cfg(feature = "mysql")]
+    #[test]
+    fn repeated_names_are_bound_again() {
+        let (query, values) = question(
+            "SELECT * FROM t WHERE a = :a OR b = :b OR c = :a",
+            json!({ "a": 1, "b": 2 }),
+        )
+        .unwrap();
+        assert_eq!(query, "SELECT * FROM t WHERE a = ? OR b = ? OR c = ?");
+        assert_eq!(values, vec![json!(1), json!(2), json!(1)]);
+    }
+
+    #[cfg(any(feature = "sqlite", feature = "postgres"))]
+    #[test]
+    fn literals_are_skipped() {
+        let (query, values) = numbered(
+            r#"SELECT ':a', ":a", `:a`, 'it''s :a', "a "":a" FROM t WHERE a = :a"#,
+            json!({ "a": 1 }),
+        )
+        .unwrap();
+        assert_eq!(
+            query,
+            r#"SELECT ':a', ":a", `:a`, 'it''s :a', "a "":a" FROM t WHERE a = $1"#
+        );
+        assert_eq!(values, vec![json!(1)]);
+    }
+
+    #[cfg(any(feature = "sqlite", feature = "postgres"))]
+    #[test]
+    fn backslashes_only_escape_in_escape_strings() {
+        let (query, _) = numbered(r"SELECT 'C:\' || :a", json!({ "a": 1 })).unwrap();
+        assert_eq!(query, r"SELECT 'C:\' || $1");
+        let (query, _) = numbered(r"SELECT E'it\'s :a' || :a", json!({ "a": 1 })).unwrap();
+        assert_eq!(query, r"SELECT E'it\'s :a' || $1");
+    }
+
+    #[cfg(feature = "mysql")]
+    #[test]
+    fn backslashes_escape_in_mysql_strings() {
+        let (query, values) = question(
+            r#"SELECT 'it\'s :a', "say \":a\"", 'C:\\' FROM t WHERE a = :a"#,
+            json!({ "a": 1 }),
+        )
+        .unwrap();
+        assert_eq!(
+            query,
+            r#"SELECT 'it\'s :a', "say \":a\"", 'C:\\' FROM t WHERE a = ?"#
+        );
+        assert_eq!(values, vec![json!(1)]);
+    }
+
+    #[cfg(any(feature = "sqlite", feature = "postgres"))]
+    #[test]
+    fn comments_are_skipped() {
+        let (query, values) =
+            numbered("SELECT :a -- :b\n/* :b */ FROM t /* :b", json!({ "a": 1 })).unwrap();
+        assert_eq!(query, "SELECT $1 -- :b\n/* :b */ FROM t /* :b");
+        assert_eq!(values, vec![json!(1)]);
+    }
+
+    #[cfg(any(feature = "sqlite", feature = "postgres"))]
+    #[test]
+    fn casts_are_not_placeholders() {
+        let (query, values) =
+            numbered("SELECT :a::int, b::text, c[1:2] FROM t", json!({ "a": 1 })).unwrap();
+        assert_eq!(query, "SELECT $1::int, b::text, c[1:2] FROM t");
+        assert_eq!(values, vec![json!(1)]);
+    }
+
+    #[cfg(any(feature = "sqlite", feature = "postgres"))]
+    #[test]
+    fn dollar_quoted_bodies_are_skipped() {
+        let (query, values) = numbered(
+            "SELECT $$ :b $$, $tag$ it's :b $tag$, :a",
+            json!({ "a": 1 }),
+        )
+        .unwrap();
+        assert_eq!(query, "SELECT $$ :b $$, $tag$ it's :b $tag$, $1");
+        assert_eq!(values, vec![json!(1)]);
+    }
+
+    #[cfg(feature = "mysql")]
+    #[test]
+    fn variables_are_kept() {
+        let (query, values) = question(
+            "SELECT @@version, @total, @a FROM t",
+            json!({ "a": 1, "version": 2 }),
+        )
+        .unwrap();
+        assert_eq!(query, "SELECT @@version, @total, ? FROM t");
+        assert_eq!(values, vec![json!(1)]);
+    }
+
+    #[cfg(any(feature = "sqlite", feature = "postgres"))]
+    #[test]
+    fn unknown_names_are_rejected() {
+        let error = numbered("SELECT :a, :b", json!({ "a": 1 })).unwrap_err();
+        assert!(matches!(error, Error::MissingNamedValue(name) if name == "b"));
+        let (query, _) = numbered("SELECT :a, @b", json!({ "a": 1 })).unwrap();
+        assert_eq!(query, "SELECT $1, @b");
+    }
+
+    #[cfg(feature = "postgres")]
+    #[test]
+    fn postgres_statements_differ_by_bound_types() {
+        let statement = |values: JsonValue| {
+            let JsonValue::Array(values) = values else {
+                panic!("positional values must be an array");
+            };
+            Postgres::statement("SELECT $1, $2", &values).into_owned()
+        };
+
+        assert_eq!(
+            Postgres::statement("SELECT 1", &[]),
+            Cow::Borrowed("SELECT 1")
+        );
+        assert_eq!(
+            statement(json!([10, "a"])),
+            "SELECT $1, $2\n/* integer, text */"
+        );
+        assert_eq!(statement(json!([10, "a"])), statement(json!([-3, "b"])));
+        assert_ne!(statement(json!([10, "a"])), statement(json!([10.5, "a"])));
+        assert_ne!(
+            statement(json!([[1, 2], null])),
+            statement(json!([["a"], null]))
+        );
+        assert_eq!(
+            statement(json!([null, { "type": "integer", "value": null }])),
+            "SELECT $1, $2\n/* null, null */"
+        );
+        assert_eq!(
+            statement(json!([{ "type": "bigint", "value": "1" }, { "type": "other", "value": 1 }])),
+            "SELECT $1, $2\n/* bigint, json */"
+        );
+    }
+}
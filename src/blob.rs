@@ -0,0 +1,226 @@
+// Copyright 2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+#[cfg(feature = "sqlite")]
+use std::{
+    ffi::{CStr, CString},
+    os::raw::c_int,
+    ptr::{self, NonNull},
+};
+
+#[cfg(feature = "sqlite")]
+use libsqlite3_sys as ffi;
+#[cfg(feature = "sqlite")]
+use sqlx::{pool::PoolConnection, sqlite::Sqlite};
+#[cfg(feature = "sqlite")]
+use tauri::async_runtime::spawn;
+
+#[cfg(feature = "sqlite")]
+use crate::Error;
+use crate::Result;
+
+/// A value opened for incremental I/O, which reads and writes byte ranges
+/// of it without loading the whole value. Only SQLite supports it.
+pub(crate) enum DbBlob {
+    #[cfg(feature = "sqlite")]
+    Sqlite(SqliteBlob),
+}
+
+impl DbBlob {
+    /// The size of the value in bytes, which cannot be changed through
+    /// incremental I/O.
+    pub(crate) fn size(&self) -> usize {
+        match *self {
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(ref blob) => blob.size,
+        }
+    }
+
+    #[allow(unused_variables)]
+    pub(crate) async fn read(&mut self, offset: usize, length: usize) -> Result<Vec<u8>> {
+        match *self {
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(ref mut blob) => blob.read(offset, length).await,
+        }
+    }
+
+    #[allow(unused_variables)]
+    pub(crate) async fn write(&mut self, offset: usize, data: &[u8]) -> Result<()> {
+        match *self {
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(ref mut blob) => blob.write(offset, data).await,
+        }
+    }
+
+    /// Closes the blob and returns its connection to the pool.
+    pub(crate) async fn close(&mut self) -> Result<()> {
+        match *self {
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(ref mut blob) => blob.close().await,
+        }
+    }
+}
+
+/// A `sqlite3_blob` handle, only used while holding the lock of the
+/// connection it was opened on.
+#[cfg(feature = "sqlite")]
+#[derive(Clone, Copy)]
+struct RawBlob(NonNull<ffi::sqlite3_blob>);
+
+// SAFETY: the handle is only used while holding the lock of its connection,
+// which serializes access to it across threads
+#[cfg(feature = "sqlite")]
+unsafe impl Send for RawBlob {}
+
+/// A SQLite blob, holding on to the pool connection it was opened on
+/// until it is closed.
+///
+/// Dropping it without calling [`SqliteBlob::close`] closes it in the
+/// background.
+#[cfg(feature = "sqlite")]
+pub(crate) struct SqliteBlob {
+    conn: Option<PoolConnection<Sqlite>>,
+    raw: RawBlob,
+    size: usize,
+}
+
+#[cfg(feature = "sqlite")]
+impl SqliteBlob {
+    /// Opens the value of `column` in the row `row_id` of `table`, see
+    /// <https://www.sqlite.org/c3ref/blob_open.html>.
+    pub(crate) async fn open(
+        mut conn: PoolConnection<Sqlite>,
+        table: &str,
+        column: &str,
+        row_id: i64,
+        writable: bool,
+    ) -> Result<Self> {
+        let table = c_string(table)?;
+        let column = c_string(column)?;
+
+        let (raw, size) = {
+            let mut handle = conn.lock_handle().await?;
+            let db = handle.as_raw_handle().as_ptr();
+            let mut raw = ptr::null_mut();
+            // SAFETY: `db` is locked and the strings outlive the call
+            let rc = unsafe {
+                ffi::sqlite3_blob_open(
+                    db,
+                    c"main".as_ptr(),
+                    table.as_ptr(),
+                    column.as_ptr(),
+                    row_id,
+                    c_int::from(writable),
+                    &mut raw,
+                )
+            };
+            check(db, rc)?;
+            let raw = NonNull::new(raw).ok_or_else(|| Error::Blob("no blob opened".into()))?;
+            // SAFETY: `raw` was just opened on the locked `db`
+            let size = unsafe { ffi::sqlite3_blob_bytes(raw.as_ptr()) };
+            (RawBlob(raw), size as usize)
+        };
+
+        Ok(Self {
+            conn: Some(conn),
+            raw,
+            size,
+        })
+    }
+
+    async fn read(&mut self, offset: usize, length: usize) -> Result<Vec<u8>> {
+        let (offset, length) = self.range(offset, length)?;
+        let mut data = vec![0; length as usize];
+        let Self { conn, raw, .. } = self;
+        let mut handle = conn.as_mut().ok_or_else(closed)?.lock_handle().await?;
+        let db = handle.as_raw_handle().as_ptr();
+        // SAFETY: the connection of `raw` is locked and `data` holds
+        // `length` bytes
+        let rc = unsafe {
+            ffi::sqlite3_blob_read(raw.0.as_ptr(), data.as_mut_ptr().cast(), length, offset)
+        };
+        check(db, rc)?;
+        Ok(data)
+    }
+
+    async fn write(&mut self, offset: usize, data: &[u8]) -> Result<()> {
+        let (offset, length) = self.range(offset, data.len())?;
+        let Self { conn, raw, .. } = self;
+        let mut handle = conn.as_mut().ok_or_else(closed)?.lock_handle().await?;
+        let db = handle.as_raw_handle().as_ptr();
+        // SAFETY: the connection of `raw` is locked and `data` holds
+        // `length` bytes
+        let rc = unsafe {
+            ffi::sqlite3_blob_write(raw.0.as_ptr(), data.as_ptr().cast(), length, offset)
+        };
+        check(db, rc)
+    }
+
+    async fn close(&mut self) -> Result<()> {
+        let conn = self.conn.take().ok_or_else(closed)?;
+        close(conn, self.raw).await
+    }
+
+    /// Checks that the range fits in the blob, as SQLite only reads and
+    /// writes whole ranges.
+    fn range(&self, offset: usize, length: usize) -> Result<(c_int, c_int)> {
+        match offset.checked_add(length) {
+            Some(end) if end <= self.size => {
+                // the size of a blob fits in a `c_int`
+                Ok((offset as c_int, length as c_int))
+            }
+            _ => Err(Error::Blob(format!(
+                "range {offset}..{} is out of the {} bytes of the blob",
+                offset.saturating_add(length),
+                self.size
+            ))),
+        }
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl Drop for SqliteBlob {
+    fn drop(&mut self) {
+        if let Some(conn) = self.conn.take() {
+            let raw = self.raw;
+            spawn(async move {
+                if let Err(e) = close(conn, raw).await {
+                    log::error!("failed to close blob: {e}");
+                }
+            });
+        }
+    }
+}
+
+#[cfg(feature = "sqlite")]
+async fn close(mut conn: PoolConnection<Sqlite>, raw: RawBlob) -> Result<()> {
+    let mut handle = conn.lock_handle().await?;
+    let db = handle.as_raw_handle().as_ptr();
+    // SAFETY: the connection of `raw` is locked, and `raw` is not used
+    // after this call
+    let rc = unsafe { ffi::sqlite3_blob_close(raw.0.as_ptr()) };
+    check(db, rc)
+}
+
+#[cfg(feature = "sqlite")]
+fn c_string(name: &str) -> Result<CString> {
+    CString::new(name).map_err(|_| Error::Blob(format!("invalid name: {name:?}")))
+}
+
+#[cfg(feature = "sqlite")]
+fn closed() -> Error {
+    Error::Blob("the blob is closed".into())
+}
+
+/// Turns a SQLite result code into the error message of the connection.
+#[cfg(feature = "sqlite")]
+fn check(db: *mut ffi::sqlite3, rc: c_int) -> Result<()> {
+    if rc == ffi::SQLITE_OK {
+        return Ok(());
+    }
+    // SAFETY: `db` is locked, and the message is copied before anything
+    // else runs on the connection
+    let message = unsafe { CStr::from_ptr(ffi::sqlite3_errmsg(db)) };
+    Err(Error::Blob(message.to_string_lossy().into_owned()))
+}
@@ -1,15 +1,79 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::Deserialize;
+use serde_json::Value as JsonValue;
+
 #[cfg(feature = "mysql")]
-mod mysql;
+pub(crate) mod mysql;
 #[cfg(feature = "postgres")]
-mod postgres;
+pub(crate) mod postgres;
 #[cfg(feature = "sqlite")]
-mod sqlite;
+pub(crate) mod sqlite;
 
-#[cfg(feature = "mysql")]
-pub(crate) use mysql::to_json;
+/// Largest integer JavaScript represents exactly (`Number.MAX_SAFE_INTEGER`).
+const MAX_SAFE_INTEGER: u64 = (1 << 53) - 1;
 
-#[cfg(feature = "postgres")]
-pub(crate) use postgres::to_json;
+/// How values without an exact JSON representation are returned to the
+/// frontend.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DecodeOptions {
+    pub big_numbers: BigNumbers,
+    pub binary: Binary,
+}
 
-#[cfg(feature = "sqlite")]
-pub(crate) use sqlite::to_json;
+/// How integers outside of JavaScript's safe range and decimals are
+/// returned to the frontend.
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BigNumbers {
+    /// As JSON numbers, which JavaScript rounds to the nearest double.
+    #[default]
+    Number,
+    /// As strings holding the exact value.
+    String,
+}
+
+impl BigNumbers {
+    pub(crate) fn int(self, v: i64) -> JsonValue {
+        match self {
+            Self::String if v.unsigned_abs() > MAX_SAFE_INTEGER => JsonValue::String(v.to_string()),
+            _ => JsonValue::Number(v.into()),
+        }
+    }
+
+    pub(crate) fn uint(self, v: u64) -> JsonValue {
+        match self {
+            Self::String if v > MAX_SAFE_INTEGER => JsonValue::String(v.to_string()),
+            _ => JsonValue::Number(v.into()),
+        }
+    }
+
+    /// Converts the exact text representation of a decimal.
+    pub(crate) fn decimal(self, v: String) -> JsonValue {
+        match self {
+            Self::String => JsonValue::String(v),
+            Self::Number => v.parse::<f64>().map(JsonValue::from).unwrap_or_default(),
+        }
+    }
+}
+
+/// How binary values (`BLOB`, `BYTEA`, ...) are returned to the frontend.
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Binary {
+    /// As arrays of byte values.
+    #[default]
+    Array,
+    /// As base64 strings, which are much smaller and faster to transfer.
+    Base64,
+}
+
+impl Binary {
+    pub(crate) fn encode(self, v: &[u8]) -> JsonValue {
+        match self {
+            Self::Array => {
+                JsonValue::Array(v.iter().map(|n| JsonValue::Number((*n).into())).collect())
+            }
+            Self::Base64 => JsonValue::String(STANDARD.encode(v)),
+        }
+    }
+}
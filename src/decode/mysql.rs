@@ -1,10 +1,11 @@
 use serde_json::Value as JsonValue;
-use sqlx::{mysql::MySqlValueRef, TypeInfo, Value, ValueRef};
+use sqlx::{mysql::MySqlValueRef, types::BigDecimal, TypeInfo, Value, ValueRef};
 use time::{Date, OffsetDateTime, PrimitiveDateTime, Time};
 
+use super::DecodeOptions;
 use crate::Error;
 
-pub(crate) fn to_json(v: MySqlValueRef) -> Result<JsonValue, Error> {
+pub(crate) fn to_json(v: MySqlValueRef, options: DecodeOptions) -> Result<JsonValue, Error> {
     if v.is_null() {
         return Ok(JsonValue::Null);
     }
@@ -33,7 +34,7 @@ pub(crate) fn to_json(v: MySqlValueRef) -> Result<JsonValue, Error> {
         }
         "TINYINT" | "SMALLINT" | "INT" | "MEDIUMINT" | "BIGINT" => {
             if let Ok(v) = ValueRef::to_owned(&v).try_decode::<i64>() {
-                JsonValue::Number(v.into())
+                options.big_numbers.int(v)
             } else {
                 JsonValue::Null
             }
@@ -41,7 +42,14 @@ pub(crate) fn to_json(v: MySqlValueRef) -> Result<JsonValue, Error> {
         "TINYINT UNSIGNED" | "SMALLINT UNSIGNED" | "INT UNSIGNED" | "MEDIUMINT UNSIGNED"
         | "BIGINT UNSIGNED" | "YEAR" => {
             if let Ok(v) = ValueRef::to_owned(&v).try_decode::<u64>() {
-                JsonValue::Number(v.into())
+                options.big_numbers.uint(v)
+            } else {
+                JsonValue::Null
+            }
+        }
+        "DECIMAL" => {
+            if let Ok(v) = ValueRef::to_owned(&v).try_decode::<BigDecimal>() {
+                options.big_numbers.decimal(v.to_plain_string())
             } else {
                 JsonValue::Null
             }
@@ -84,7 +92,7 @@ pub(crate) fn to_json(v: MySqlValueRef) -> Result<JsonValue, Error> {
         "JSON" => ValueRef::to_owned(&v).try_decode().unwrap_or_default(),
         "TINIYBLOB" | "MEDIUMBLOB" | "BLOB" | "LONGBLOB" => {
             if let Ok(v) = ValueRef::to_owned(&v).try_decode::<Vec<u8>>() {
-                JsonValue::Array(v.into_iter().map(|n| JsonValue::Number(n.into())).collect())
+                options.binary.encode(&v)
             } else {
                 JsonValue::Null
             }
@@ -1,14 +1,38 @@
+use std::net::{Ipv4Addr, Ipv6Addr};
+
 use serde_json::Value as JsonValue;
-use sqlx::{postgres::PgValueRef, TypeInfo, Value, ValueRef};
-use time::{Date, OffsetDateTime, PrimitiveDateTime, Time};
+use sqlx::{
+    postgres::{PgTypeInfo, PgTypeKind, PgValueFormat, PgValueRef},
+    types::BigDecimal,
+    TypeInfo, Value, ValueRef,
+};
+use time::{Date, Duration, Month, OffsetDateTime, PrimitiveDateTime, Time};
 
+use super::DecodeOptions;
 use crate::Error;
 
-pub(crate) fn to_json(v: PgValueRef) -> Result<JsonValue, Error> {
+pub(crate) fn to_json(v: PgValueRef, options: DecodeOptions) -> Result<JsonValue, Error> {
     if v.is_null() {
         return Ok(JsonValue::Null);
     }
 
+    // the types below are decoded from their binary representation, the
+    // text representation is only used by simple queries
+    if let (Ok(bytes), PgValueFormat::Binary) = (v.as_bytes(), v.format()) {
+        match v.type_info().kind() {
+            PgTypeKind::Array(element) => {
+                return Ok(array(element, bytes, options)?.unwrap_or_default())
+            }
+            PgTypeKind::Enum(_) => return Ok(text(bytes).unwrap_or_default()),
+            _ => {}
+        }
+        if let "UUID" | "INET" | "CIDR" | "MACADDR" | "MACADDR8" | "INTERVAL" | "MONEY" =
+            v.type_info().name()
+        {
+            return Ok(raw_to_json(&v.type_info(), bytes, options)?.unwrap_or_default());
+        }
+    }
+
     let res = match v.type_info().name() {
         "CHAR" | "VARCHAR" | "TEXT" | "NAME" => {
             if let Ok(v) = ValueRef::to_owned(&v).try_decode() {
@@ -47,7 +71,14 @@ pub(crate) fn to_json(v: PgValueRef) -> Result<JsonValue, Error> {
         }
         "INT8" => {
             if let Ok(v) = ValueRef::to_owned(&v).try_decode::<i64>() {
-                JsonValue::Number(v.into())
+                options.big_numbers.int(v)
+            } else {
+                JsonValue::Null
+            }
+        }
+        "NUMERIC" => {
+            if let Ok(v) = ValueRef::to_owned(&v).try_decode::<BigDecimal>() {
+                options.big_numbers.decimal(v.to_plain_string())
             } else {
                 JsonValue::Null
             }
@@ -90,14 +121,503 @@ pub(crate) fn to_json(v: PgValueRef) -> Result<JsonValue, Error> {
         "JSON" | "JSONB" => ValueRef::to_owned(&v).try_decode().unwrap_or_default(),
         "BYTEA" => {
             if let Ok(v) = ValueRef::to_owned(&v).try_decode::<Vec<u8>>() {
-                JsonValue::Array(v.into_iter().map(|n| JsonValue::Number(n.into())).collect())
+                options.binary.encode(&v)
             } else {
                 JsonValue::Null
             }
         }
         "VOID" => JsonValue::Null,
+        "UUID" | "INET" | "CIDR" | "MACADDR" | "MACADDR8" | "INTERVAL" | "MONEY" => {
+            if let Ok(v) = v.as_str() {
+                JsonValue::String(v.to_string())
+            } else {
+                JsonValue::Null
+            }
+        }
         _ => return Err(Error::UnsupportedDatatype(v.type_info().name().to_string())),
     };
 
     Ok(res)
 }
+
+/// Decodes a value from its binary representation, for the elements of
+/// arrays and the types sqlx can only decode with optional dependencies.
+///
+/// Returns `None` if the value is malformed.
+fn raw_to_json(
+    type_info: &PgTypeInfo,
+    bytes: &[u8],
+    options: DecodeOptions,
+) -> Result<Option<JsonValue>, Error> {
+    match type_info.kind() {
+        PgTypeKind::Array(element) => return array(element, bytes, options),
+        PgTypeKind::Enum(_) => return Ok(text(bytes)),
+        _ => {}
+    }
+
+    let res = match type_info.name() {
+        "CHAR" | "VARCHAR" | "TEXT" | "NAME" => text(bytes),
+        "FLOAT4" => be_bytes(bytes).map(f32::from_be_bytes).map(JsonValue::from),
+        "FLOAT8" => be_bytes(bytes).map(f64::from_be_bytes).map(JsonValue::from),
+        "INT2" => be_bytes(bytes).map(i16::from_be_bytes).map(JsonValue::from),
+        "INT4" => be_bytes(bytes).map(i32::from_be_bytes).map(JsonValue::from),
+        "INT8" => be_bytes(bytes)
+            .map(i64::from_be_bytes)
+            .map(|v| options.big_numbers.int(v)),
+        "NUMERIC" => numeric(bytes).map(|v| options.big_numbers.decimal(v)),
+        "BOOL" => match bytes {
+            [v] => Some(JsonValue::Bool(*v != 0)),
+            _ => None,
+        },
+        "DATE" => be_bytes(bytes)
+            .map(i32::from_be_bytes)
+            .and_then(|days| pg_epoch().date().checked_add(Duration::days(days.into())))
+            .map(|v| JsonValue::String(v.to_string())),
+        "TIME" => be_bytes(bytes)
+            .map(i64::from_be_bytes)
+            .map(|micros| Time::MIDNIGHT + Duration::microseconds(micros))
+            .map(|v| JsonValue::String(v.to_string())),
+        "TIMESTAMP" => timestamp(bytes).map(|v| JsonValue::String(v.to_string())),
+        "TIMESTAMPTZ" => timestamp(bytes).map(|v| JsonValue::String(v.assume_utc().to_string())),
+        "JSON" => serde_json::from_slice(bytes).ok(),
+        // binary JSONB is prefixed with its format version
+        "JSONB" => bytes
+            .split_first()
+            .and_then(|(_, v)| serde_json::from_slice(v).ok()),
+        "BYTEA" => Some(options.binary.encode(bytes)),
+        "UUID" => uuid(bytes),
+        "INET" | "CIDR" => inet(bytes),
+        "MACADDR" | "MACADDR8" => mac_address(bytes),
+        "INTERVAL" => interval(bytes),
+        "MONEY" => be_bytes(bytes)
+            .map(i64::from_be_bytes)
+            .map(|v| options.big_numbers.decimal(money(v))),
+        _ => return Err(Error::UnsupportedDatatype(type_info.name().to_string())),
+    };
+
+    Ok(res)
+}
+
+fn be_bytes<const N: usize>(bytes: &[u8]) -> Option<[u8; N]> {
+    bytes.try_into().ok()
+}
+
+fn text(bytes: &[u8]) -> Option<JsonValue> {
+    std::str::from_utf8(bytes)
+        .ok()
+        .map(|v| JsonValue::String(v.to_string()))
+}
+
+/// Postgres dates and timestamps count from 2000-01-01.
+fn pg_epoch() -> PrimitiveDateTime {
+    Date::from_calendar_date(2000, Month::January, 1)
+        .unwrap()
+        .midnight()
+}
+
+fn timestamp(bytes: &[u8]) -> Option<PrimitiveDateTime> {
+    let micros = i64::from_be_bytes(be_bytes(bytes)?);
+    pg_epoch().checked_add(Duration::microseconds(micros))
+}
+
+/// Decodes an array, possibly multidimensional, to nested JSON arrays.
+fn array(
+    element: &PgTypeInfo,
+    bytes: &[u8],
+    options: DecodeOptions,
+) -> Result<Option<JsonValue>, Error> {
+    let mut reader = Reader(bytes);
+    let Some(header) = reader.array_header() else {
+        return Ok(None);
+    };
+    if header.is_empty() {
+        return Ok(Some(JsonValue::Array(Vec::new())));
+    }
+    array_dimension(&mut reader, element, &header, options)
+}
+
+fn array_dimension(
+    reader: &mut Reader<'_>,
+    element: &PgTypeInfo,
+    dimensions: &[usize],
+    options: DecodeOptions,
+) -> Result<Option<JsonValue>, Error> {
+    let mut values = Vec::with_capacity(dimensions[0]);
+    for _ in 0..dimensions[0] {
+        let value = if dimensions.len() > 1 {
+            array_dimension(reader, element, &dimensions[1..], options)?
+        } else {
+            match reader.element() {
+                Some(Some(bytes)) => raw_to_json(element, bytes, options)?,
+                Some(None) => Some(JsonValue::Null),
+                None => None,
+            }
+        };
+        let Some(value) = value else {
+            return Ok(None);
+        };
+        values.push(value);
+    }
+    Ok(Some(JsonValue::Array(values)))
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        if self.0.len() < len {
+            return None;
+        }
+        let (head, tail) = self.0.split_at(len);
+        self.0 = tail;
+        Some(head)
+    }
+
+    fn i32(&mut self) -> Option<i32> {
+        self.take(4).and_then(be_bytes).map(i32::from_be_bytes)
+    }
+
+    /// Reads the header of an array and returns the length of each dimension.
+    fn array_header(&mut self) -> Option<Vec<usize>> {
+        let dimensions = self.i32()?;
+        // null flag and element type
+        self.take(8)?;
+        (0..dimensions)
+            .map(|_| {
+                let len = self.i32()?;
+                // lower bound
+                self.i32()?;
+                len.try_into().ok()
+            })
+            .collect()
+    }
+
+    /// Reads an element of an array, `Some(None)` being `NULL`.
+    fn element(&mut self) -> Option<Option<&'a [u8]>> {
+        match self.i32()? {
+            -1 => Some(None),
+            len => self.take(len.try_into().ok()?).map(Some),
+        }
+    }
+}
+
+/// The largest display scale of a `NUMERIC`.
+const NUMERIC_MAX_SCALE: i16 = 0x3FFF;
+
+/// Formats a binary `NUMERIC` without losing precision.
+fn numeric(bytes: &[u8]) -> Option<String> {
+    let mut reader = Reader(bytes);
+    let mut i16 = || reader.take(2).and_then(be_bytes).map(i16::from_be_bytes);
+    let digits = i16()?;
+    let weight = i16()?;
+    let sign = i16()? as u16;
+    let scale = i16()?;
+    match sign {
+        0x0000 | 0x4000 => {}
+        0xC000 => return Some("NaN".to_string()),
+        0xD000 => return Some("Infinity".to_string()),
+        0xF000 => return Some("-Infinity".to_string()),
+        _ => return None,
+    }
+    if !(0..=NUMERIC_MAX_SCALE).contains(&scale) {
+        return None;
+    }
+    let scale = scale as usize;
+    // each digit is a base 10000 group
+    let digits = (0..digits)
+        .map(|_| i16().filter(|digit| (0..10_000).contains(digit)))
+        .collect::<Option<Vec<_>>>()?;
+    // the positions of the digits can exceed an `i16`
+    let weight = i32::from(weight);
+
+    let mut integer = String::new();
+    let mut fraction = String::new();
+    for exponent in (0..=weight.max(0)).rev() {
+        let index = usize::try_from(weight - exponent).ok();
+        let digit = index.and_then(|i| digits.get(i)).copied().unwrap_or(0);
+        if integer.is_empty() {
+            integer = digit.to_string();
+        } else {
+            integer.push_str(&format!("{digit:04}"));
+        }
+    }
+    for exponent in 1..=scale.div_ceil(4) as i32 {
+        let index = usize::try_from(weight + exponent).ok();
+        let digit = index.and_then(|i| digits.get(i)).copied().unwrap_or(0);
+        fraction.push_str(&format!("{digit:04}"));
+    }
+    fraction.truncate(scale);
+
+    let sign = if sign == 0x4000 { "-" } else { "" };
+    if fraction.is_empty() {
+        Some(format!("{sign}{integer}"))
+    } else {
+        Some(format!("{sign}{integer}.{fraction}"))
+    }
+}
+
+fn uuid(bytes: &[u8]) -> Option<JsonValue> {
+    let bytes: [u8; 16] = be_bytes(bytes)?;
+    let hex: String = bytes.iter().map(|b| format!("{b:02x}")).collect();
+    Some(JsonValue::String(format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )))
+}
+
+fn inet(bytes: &[u8]) -> Option<JsonValue> {
+    let [family, bits, is_cidr, len, address @ ..] = bytes else {
+        return None;
+    };
+    if usize::from(*len) != address.len() {
+        return None;
+    }
+    let (address, max_bits) = match family {
+        2 => (Ipv4Addr::from(be_bytes::<4>(address)?).to_string(), 32),
+        3 => (Ipv6Addr::from(be_bytes::<16>(address)?).to_string(), 128),
+        _ => return None,
+    };
+    // like Postgres, hosts of `INET` values are shown without their netmask
+    if *is_cidr == 0 && *bits == max_bits {
+        Some(JsonValue::String(address))
+    } else {
+        Some(JsonValue::String(format!("{address}/{bits}")))
+    }
+}
+
+fn mac_address(bytes: &[u8]) -> Option<JsonValue> {
+    if bytes.len() != 6 && bytes.len() != 8 {
+        return None;
+    }
+    let parts: Vec<String> = bytes.iter().map(|b| format!("{b:02x}")).collect();
+    Some(JsonValue::String(parts.join(":")))
+}
+
+/// Formats an `INTERVAL` as an ISO-8601 duration, the way Postgres does with
+/// `intervalstyle` set to `iso_8601`.
+fn interval(bytes: &[u8]) -> Option<JsonValue> {
+    let mut reader = Reader(bytes);
+    let micros = i64::from_be_bytes(reader.take(8).and_then(be_bytes)?);
+    let days = reader.i32()?;
+    let months = reader.i32()?;
+
+    let mut res = String::from("P");
+    for (value, unit) in [(months / 12, 'Y'), (months % 12, 'M'), (days, 'D')] {
+        if value != 0 {
+            res.push_str(&format!("{value}{unit}"));
+        }
+    }
+    if micros != 0 {
+        let sign = if micros < 0 { "-" } else { "" };
+        let micros = micros.unsigned_abs();
+        let hours = micros / 3_600_000_000;
+        let minutes = micros / 60_000_000 % 60;
+        let seconds = micros / 1_000_000 % 60;
+        let fraction = micros % 1_000_000;
+
+        res.push('T');
+        if hours != 0 {
+            res.push_str(&format!("{sign}{hours}H"));
+        }
+        if minutes != 0 {
+            res.push_str(&format!("{sign}{minutes}M"));
+        }
+        if fraction != 0 {
+            let fraction = format!("{fraction:06}");
+            res.push_str(&format!(
+                "{sign}{seconds}.{}S",
+                fraction.trim_end_matches('0')
+            ));
+        } else if seconds != 0 {
+            res.push_str(&format!("{sign}{seconds}S"));
+        }
+    }
+    if res == "P" {
+        res.push_str("T0S");
+    }
+
+    Some(JsonValue::String(res))
+}
+
+/// Formats a `MONEY` amount, stored in cents.
+fn money(cents: i64) -> String {
+    let sign = if cents < 0 { "-" } else { "" };
+    let cents = cents.unsigned_abs();
+    format!("{sign}{}.{:02}", cents / 100, cents % 100)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::{Postgres, Type};
+
+    fn numeric_bytes(weight: i16, sign: u16, scale: i16, digits: &[i16]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend((digits.len() as i16).to_be_bytes());
+        bytes.extend(weight.to_be_bytes());
+        bytes.extend(sign.to_be_bytes());
+        bytes.extend(scale.to_be_bytes());
+        for digit in digits {
+            bytes.extend(digit.to_be_bytes());
+        }
+        bytes
+    }
+
+    fn interval_bytes(micros: i64, days: i32, months: i32) -> Vec<u8> {
+        let mut bytes = micros.to_be_bytes().to_vec();
+        bytes.extend(days.to_be_bytes());
+        bytes.extend(months.to_be_bytes());
+        bytes
+    }
+
+    fn string(value: &str) -> Option<JsonValue> {
+        Some(JsonValue::String(value.to_string()))
+    }
+
+    #[test]
+    fn numeric_special_values() {
+        assert_eq!(numeric(&numeric_bytes(0, 0xC000, 0, &[])).unwrap(), "NaN");
+        assert_eq!(
+            numeric(&numeric_bytes(0, 0xD000, 0, &[])).unwrap(),
+            "Infinity"
+        );
+        assert_eq!(
+            numeric(&numeric_bytes(0, 0xF000, 0, &[])).unwrap(),
+            "-Infinity"
+        );
+        assert_eq!(numeric(&numeric_bytes(0, 0x1234, 0, &[])), None);
+    }
+
+    #[test]
+    fn numeric_digits() {
+        // 12345678.9
+        assert_eq!(
+            numeric(&numeric_bytes(1, 0x0000, 1, &[1234, 5678, 9000])).unwrap(),
+            "12345678.9"
+        );
+        assert_eq!(
+            numeric(&numeric_bytes(-1, 0x4000, 7, &[1, 2340])).unwrap(),
+            "-0.0001234"
+        );
+        // the first group after the point being implied
+        assert_eq!(
+            numeric(&numeric_bytes(-2, 0x4000, 8, &[1234])).unwrap(),
+            "-0.00001234"
+        );
+        // 1000000, trailing zero groups being implied
+        assert_eq!(
+            numeric(&numeric_bytes(1, 0x0000, 0, &[100])).unwrap(),
+            "1000000"
+        );
+        // a scale shorter than the digits truncates them
+        assert_eq!(
+            numeric(&numeric_bytes(0, 0x0000, 2, &[1, 2345])).unwrap(),
+            "1.23"
+        );
+    }
+
+    #[test]
+    fn numeric_rejects_invalid_scale_and_digits() {
+        assert_eq!(numeric(&numeric_bytes(0, 0x0000, -1, &[1])), None);
+        assert_eq!(numeric(&numeric_bytes(0, 0x0000, 0x4000, &[1])), None);
+        assert_eq!(numeric(&numeric_bytes(0, 0x0000, 0, &[10_000])), None);
+        assert_eq!(numeric(&numeric_bytes(0, 0x0000, 0, &[-1])), None);
+    }
+
+    #[test]
+    fn inet_addresses() {
+        assert_eq!(inet(&[2, 32, 0, 4, 192, 168, 0, 1]), string("192.168.0.1"));
+        assert_eq!(
+            inet(&[2, 24, 0, 4, 192, 168, 0, 1]),
+            string("192.168.0.1/24")
+        );
+        assert_eq!(inet(&[2, 32, 1, 4, 10, 0, 0, 1]), string("10.0.0.1/32"));
+
+        let mut localhost = vec![3, 128, 0, 16];
+        localhost.extend(Ipv6Addr::LOCALHOST.octets());
+        assert_eq!(inet(&localhost), string("::1"));
+        let mut network = vec![3, 32, 1, 16];
+        network.extend("2001:db8::".parse::<Ipv6Addr>().unwrap().octets());
+        assert_eq!(inet(&network), string("2001:db8::/32"));
+    }
+
+    #[test]
+    fn intervals() {
+        assert_eq!(
+            interval(&interval_bytes(3_723_500_000, 4, 14)),
+            string("P1Y2M4DT1H2M3.5S")
+        );
+        assert_eq!(interval(&interval_bytes(0, 0, 0)), string("PT0S"));
+        assert_eq!(
+            interval(&interval_bytes(-3_723_500_000, -4, -14)),
+            string("P-1Y-2M-4DT-1H-2M-3.5S")
+        );
+        // each field keeps its own sign
+        assert_eq!(
+            interval(&interval_bytes(-60_000_000, -3, 1)),
+            string("P1M-3DT-1M")
+        );
+        assert_eq!(
+            interval(&interval_bytes(1_000_000, 2, -13)),
+            string("P-1Y-1M2DT1S")
+        );
+    }
+
+    #[test]
+    fn two_dimensional_array_with_nulls() {
+        let int4 = <i32 as Type<Postgres>>::type_info();
+        let mut bytes = Vec::new();
+        // dimensions, null flag and element type
+        for v in [2, 1, 23] {
+            bytes.extend(i32::to_be_bytes(v));
+        }
+        // length and lower bound of each dimension
+        for v in [2, 1, 2, 1] {
+            bytes.extend(i32::to_be_bytes(v));
+        }
+        for element in [Some(1), None, None, Some(4)] {
+            match element {
+                Some(v) => {
+                    bytes.extend(4_i32.to_be_bytes());
+                    bytes.extend(i32::to_be_bytes(v));
+                }
+                None => bytes.extend((-1_i32).to_be_bytes()),
+            }
+        }
+
+        let options = DecodeOptions::default();
+        assert_eq!(
+            array(&int4, &bytes, options).unwrap(),
+            Some(serde_json::json!([[1, null], [null, 4]]))
+        );
+        let int4_array = <Vec<i32> as Type<Postgres>>::type_info();
+        assert_eq!(
+            raw_to_json(&int4_array, &bytes, options).unwrap(),
+            Some(serde_json::json!([[1, null], [null, 4]]))
+        );
+        // missing the last element
+        assert_eq!(
+            array(&int4, &bytes[..bytes.len() - 8], options).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn truncated_values() {
+        let options = DecodeOptions::default();
+        let int4 = <i32 as Type<Postgres>>::type_info();
+        assert_eq!(raw_to_json(&int4, &[0, 0, 1], options).unwrap(), None);
+        assert_eq!(numeric(&numeric_bytes(1, 0x0000, 0, &[1, 2])[..10]), None);
+        assert_eq!(numeric(&[0, 1, 0]), None);
+        assert_eq!(inet(&[2, 32, 0, 4, 192, 168, 0]), None);
+        assert_eq!(inet(&[3, 128, 0, 16, 0, 0]), None);
+        assert_eq!(interval(&interval_bytes(1, 2, 3)[..15]), None);
+        assert_eq!(uuid(&[0; 15]), None);
+        assert_eq!(mac_address(&[0; 7]), None);
+        assert_eq!(array(&int4, &[0, 0, 0, 1, 0, 0], options).unwrap(), None);
+    }
+}
@@ -2,9 +2,10 @@ use serde_json::Value as JsonValue;
 use sqlx::{sqlite::SqliteValueRef, TypeInfo, Value, ValueRef};
 use time::{Date, PrimitiveDateTime, Time};
 
+use super::DecodeOptions;
 use crate::Error;
 
-pub(crate) fn to_json(v: SqliteValueRef) -> Result<JsonValue, Error> {
+pub(crate) fn to_json(v: SqliteValueRef, options: DecodeOptions) -> Result<JsonValue, Error> {
     if v.is_null() {
         return Ok(JsonValue::Null);
     }
@@ -26,7 +27,7 @@ pub(crate) fn to_json(v: SqliteValueRef) -> Result<JsonValue, Error> {
         }
         "INTEGER" | "NUMERIC" => {
             if let Ok(v) = v.to_owned().try_decode::<i64>() {
-                JsonValue::Number(v.into())
+                options.big_numbers.int(v)
             } else {
                 JsonValue::Null
             }
@@ -61,7 +62,7 @@ pub(crate) fn to_json(v: SqliteValueRef) -> Result<JsonValue, Error> {
         }
         "BLOB" => {
             if let Ok(v) = v.to_owned().try_decode::<Vec<u8>>() {
-                JsonValue::Array(v.into_iter().map(|n| JsonValue::Number(n.into())).collect())
+                options.binary.encode(&v)
             } else {
                 JsonValue::Null
             }
@@ -2,18 +2,17 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-License-Identifier: MIT
 
-#[cfg(any(
-    all(feature = "sqlite", feature = "mysql"),
-    all(feature = "sqlite", feature = "postgres"),
-    all(feature = "mysql", feature = "postgres")
-))]
-compile_error!("Only one database driver can be enabled. Use `default-features = false` and set the feature flag for the driver of your choice.");
-
 #[cfg(not(any(feature = "sqlite", feature = "mysql", feature = "postgres")))]
 compile_error!(
     "Database driver not defined. Please set the feature flag for the driver of your choice."
 );
 
+mod bind;
+mod blob;
 mod decode;
 mod plugin;
+mod scope;
+mod wrapper;
+pub use decode::{BigNumbers, Binary, DecodeOptions};
 pub use plugin::*;
+pub use wrapper::{DbPool, LastInsertId};
@@ -2,39 +2,43 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-License-Identifier: MIT
 
+use base64::{engine::general_purpose::STANDARD, Engine};
 use futures_core::future::BoxFuture;
 use serde::{ser::Serializer, Deserialize, Serialize};
 use serde_json::Value as JsonValue;
 use sqlx::{
     error::BoxDynError,
-    migrate::{
-        MigrateDatabase, Migration as SqlxMigration, MigrationSource, MigrationType, Migrator,
-    },
-    Column, Pool, Row,
+    migrate::{MigrateError, Migration as SqlxMigration, MigrationSource, MigrationType, Migrator},
 };
 use tauri::{
+    async_runtime::spawn,
     command,
     plugin::{Builder as PluginBuilder, TauriPlugin},
     AppHandle, Manager, RunEvent, Runtime, State,
 };
-use tokio::sync::Mutex;
-
-use std::collections::HashMap;
+use tokio::sync::{Mutex, OwnedMappedMutexGuard, OwnedMutexGuard, RwLock};
 
-#[cfg(feature = "sqlite")]
-use std::{fs::create_dir_all, path::PathBuf};
+use std::{
+    collections::HashMap,
+    future::Future,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+};
 
-#[cfg(feature = "sqlite")]
-type Db = sqlx::sqlite::Sqlite;
-#[cfg(feature = "mysql")]
-type Db = sqlx::mysql::MySql;
-#[cfg(feature = "postgres")]
-type Db = sqlx::postgres::Postgres;
+/// Number of rows per chunk returned by `fetch_next` when `select_stream`
+/// is not given a chunk size.
+const DEFAULT_CHUNK_SIZE: usize = 100;
 
-#[cfg(feature = "sqlite")]
-type LastInsertId = i64;
-#[cfg(not(feature = "sqlite"))]
-type LastInsertId = u64;
+use crate::bind::BindValues;
+use crate::blob::DbBlob;
+use crate::decode::{BigNumbers, Binary, DecodeOptions};
+use crate::scope::Scope;
+use crate::wrapper::{
+    BatchStatement, ColumnarRows, ConnectionOptions, Cursor, DbPool, DbTransaction, LastInsertId,
+};
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
@@ -46,6 +50,38 @@ pub enum Error {
     DatabaseNotLoaded(String),
     #[error("unsupported datatype: {0}")]
     UnsupportedDatatype(String),
+    #[error("invalid connection url: {0}")]
+    InvalidDbUrl(String),
+    #[error("transaction {0} not found")]
+    TransactionNotFound(u32),
+    #[error("cursor {0} not found")]
+    CursorNotFound(u32),
+    #[error("no migrations registered for database {0}")]
+    MigrationsNotFound(String),
+    #[error("migration {0} has no down migration and cannot be reverted")]
+    IrreversibleMigration(i64),
+    #[error("invalid value for type {0}: {1}")]
+    InvalidValue(String, String),
+    #[error("unsupported operation: {0}")]
+    Unsupported(String),
+    #[error("failed to resolve path: {0}")]
+    PathResolution(String),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("database {0} is not allowed by the scope of the plugin")]
+    NotAllowed(String),
+    #[error("database {0} is read-only")]
+    ReadOnly(String),
+    #[error("named query {0} not found")]
+    NamedQueryNotFound(String),
+    #[error("raw queries are disabled, use named queries instead")]
+    RawQueriesDisabled,
+    #[error("no value given for the named placeholder :{0}")]
+    MissingNamedValue(String),
+    #[error("blob {0} not found")]
+    BlobNotFound(u32),
+    #[error("blob I/O failed: {0}")]
+    Blob(String),
 }
 
 impl Serialize for Error {
@@ -57,49 +93,444 @@ impl Serialize for Error {
     }
 }
 
-type Result<T> = std::result::Result<T, Error>;
+pub(crate) type Result<T> = std::result::Result<T, Error>;
 
-#[cfg(feature = "sqlite")]
-/// Resolves the App's **file path** from the `AppHandle` context
-/// object
-fn app_path<R: Runtime>(app: &AppHandle<R>) -> PathBuf {
-    #[allow(deprecated)] // FIXME: Change to non-deprecated function in Tauri v2
-    app.path_resolver()
-        .app_dir()
-        .expect("No App path was found!")
+struct DbInstance {
+    pool: DbPool,
+    read_only: bool,
 }
 
-#[cfg(feature = "sqlite")]
-/// Maps the user supplied DB connection string to a connection string
-/// with a fully qualified file path to the App's designed "app_path"
-fn path_mapper(mut app_path: PathBuf, connection_string: &str) -> String {
-    app_path.push(
-        connection_string
-            .split_once(':')
-            .expect("Couldn't parse the connection string for DB!")
-            .1,
-    );
+/// The pools of the loaded databases. Lookups clone the pool handle, so
+/// the lock is never held while a query runs.
+#[derive(Default)]
+struct DbInstances(RwLock<HashMap<String, DbInstance>>);
 
-    format!(
-        "sqlite:{}",
-        app_path
-            .to_str()
-            .expect("Problem creating fully qualified path to Database file!")
-    )
+impl DbInstances {
+    async fn get(&self, db: &str) -> Result<DbPool> {
+        self.0
+            .read()
+            .await
+            .get(db)
+            .map(|instance| instance.pool.clone())
+            .ok_or_else(|| Error::DatabaseNotLoaded(db.to_string()))
+    }
+
+    /// Whether `db` was loaded read-only, failing if it is not loaded.
+    async fn is_read_only(&self, db: &str) -> Result<bool> {
+        self.0
+            .read()
+            .await
+            .get(db)
+            .map(|instance| instance.read_only)
+            .ok_or_else(|| Error::DatabaseNotLoaded(db.to_string()))
+    }
+
+    /// Fails if `db` is not loaded or was loaded read-only.
+    async fn check_writable(&self, db: &str) -> Result<()> {
+        if self.is_read_only(db).await? {
+            Err(Error::ReadOnly(db.to_string()))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Starts a read-only transaction for a query run outside of any
+    /// transaction, if `db` was loaded read-only. With Postgres and MySQL,
+    /// the read-only mode of the session is only a default, which the query
+    /// itself could change.
+    async fn read_only_transaction(&self, db: &str) -> Result<Option<DbTransaction>> {
+        let pool = match self.0.read().await.get(db) {
+            Some(instance) if instance.read_only => instance.pool.clone(),
+            Some(_) => return Ok(None),
+            None => return Err(Error::DatabaseNotLoaded(db.to_string())),
+        };
+        Ok(Some(pool.begin_read_only().await?))
+    }
+}
+
+/// The connection settings of the plugin config.
+#[derive(Default)]
+struct ConnectionConfig {
+    /// Connection options, keyed by database.
+    options: HashMap<String, ConnectionOptions>,
+    allow_external_paths: bool,
+    scope: Scope,
+}
+
+impl ConnectionConfig {
+    /// Whether `db` is another spelling of a SQLite database made read-only
+    /// in the config, such as `sqlite:./test.db` for `sqlite:test.db`, which
+    /// would otherwise be loaded without its options.
+    #[cfg(feature = "sqlite")]
+    fn is_read_only_alias<R: Runtime>(
+        &self,
+        app: &AppHandle<R>,
+        db: &str,
+        options: &ConnectionOptions,
+    ) -> Result<bool> {
+        let Some(file) = DbPool::sqlite_file(db, app, options, self.allow_external_paths)? else {
+            return Ok(false);
+        };
+        Ok(self.options.iter().any(|(other, other_options)| {
+            other != db
+                && other_options.is_read_only()
+                // the paths of the config are trusted
+                && matches!(
+                    DbPool::sqlite_file(other, app, other_options, true),
+                    Ok(Some(other_file)) if other_file == file
+                )
+        }))
+    }
+}
+
+/// Provides the SQLCipher key of a database, given its connection url, or
+/// `None` to fall back to the key passed to `load`, if any.
+#[cfg(feature = "sqlcipher")]
+pub type KeyProvider = Arc<dyn Fn(&str) -> Option<String> + Send + Sync>;
+
+/// The SQLCipher keys set on the [`Builder`].
+#[cfg(feature = "sqlcipher")]
+#[derive(Default, Clone)]
+struct SqlCipherKeys {
+    keys: HashMap<String, String>,
+    provider: Option<KeyProvider>,
+}
+
+#[cfg(feature = "sqlcipher")]
+impl SqlCipherKeys {
+    fn get(&self, db: &str) -> Option<String> {
+        self.keys
+            .get(db)
+            .cloned()
+            .or_else(|| self.provider.as_ref().and_then(|provider| provider(db)))
+    }
+}
+
+/// The queries registered with [`Builder::add_named_query`], keyed by
+/// database then name.
+#[derive(Default)]
+struct Queries {
+    named: HashMap<String, HashMap<String, String>>,
+    raw_disabled: bool,
+}
+
+impl Queries {
+    fn named(&self, db: &str, name: &str) -> Result<&str> {
+        self.named
+            .get(db)
+            .and_then(|queries| queries.get(name))
+            .map(String::as_str)
+            .ok_or_else(|| Error::NamedQueryNotFound(name.to_string()))
+    }
+
+    fn check_raw(&self) -> Result<()> {
+        if self.raw_disabled {
+            Err(Error::RawQueriesDisabled)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// A transaction that a command can use without holding the lock on every
+/// other transaction. It is taken out once committed or rolled back.
+type SharedTransaction = Arc<Mutex<Option<DbTransaction>>>;
+
+/// Transactions started through the `begin` command, keyed by the id
+/// handed out to the frontend, along with the database they belong to.
+#[derive(Default)]
+struct Transactions {
+    next_id: AtomicU32,
+    transactions: Mutex<HashMap<u32, (String, SharedTransaction)>>,
+}
+
+impl Transactions {
+    async fn insert(&self, db: String, transaction: DbTransaction) -> u32 {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.transactions
+            .lock()
+            .await
+            .insert(id, (db, Arc::new(Mutex::new(Some(transaction)))));
+        id
+    }
+
+    /// Locks an open transaction, making sure it was started on `db`. The
+    /// other transactions stay usable while the lock is held.
+    async fn lock(
+        &self,
+        db: &str,
+        id: u32,
+    ) -> Result<OwnedMappedMutexGuard<Option<DbTransaction>, DbTransaction>> {
+        let transaction = match self.transactions.lock().await.get(&id) {
+            Some((transaction_db, transaction)) if transaction_db == db => transaction.clone(),
+            _ => return Err(Error::TransactionNotFound(id)),
+        };
+        OwnedMutexGuard::try_map(transaction.lock_owned().await, Option::as_mut)
+            .map_err(|_| Error::TransactionNotFound(id))
+    }
+
+    /// Removes a transaction to finish it, once the command using it, if
+    /// any, is done.
+    async fn take(&self, id: u32) -> Result<DbTransaction> {
+        let (_, transaction) = self
+            .transactions
+            .lock()
+            .await
+            .remove(&id)
+            .ok_or(Error::TransactionNotFound(id))?;
+        let transaction = transaction.lock().await.take();
+        transaction.ok_or(Error::TransactionNotFound(id))
+    }
+
+    /// Rolls back every open transaction of `db`.
+    async fn rollback_all(&self, db: &str) {
+        let ids: Vec<u32> = self
+            .transactions
+            .lock()
+            .await
+            .iter()
+            .filter(|(_, (transaction_db, _))| transaction_db == db)
+            .map(|(id, _)| *id)
+            .collect();
+        for id in ids {
+            if let Ok(transaction) = self.take(id).await {
+                if let Err(e) = transaction.rollback().await {
+                    log::error!("failed to roll back transaction {id}: {e}");
+                }
+            }
+        }
+    }
+}
+
+/// A blob that can be read or written without holding the lock on every
+/// other blob.
+type SharedBlob = Arc<Mutex<DbBlob>>;
+
+/// Blobs opened through the `open_blob` command, keyed by the id handed out
+/// to the frontend, along with the database they belong to.
+#[derive(Default)]
+struct Blobs {
+    next_id: AtomicU32,
+    blobs: Mutex<HashMap<u32, (String, SharedBlob)>>,
+}
+
+impl Blobs {
+    async fn get(&self, id: u32) -> Result<SharedBlob> {
+        self.blobs
+            .lock()
+            .await
+            .get(&id)
+            .map(|(_, blob)| blob.clone())
+            .ok_or(Error::BlobNotFound(id))
+    }
+
+    async fn remove(&self, id: u32) -> Result<SharedBlob> {
+        self.blobs
+            .lock()
+            .await
+            .remove(&id)
+            .map(|(_, blob)| blob)
+            .ok_or(Error::BlobNotFound(id))
+    }
+
+    /// Closes every open blob of `db`.
+    async fn close_all(&self, db: &str) {
+        let mut blobs = self.blobs.lock().await;
+        let ids: Vec<u32> = blobs
+            .iter()
+            .filter(|(_, (blob_db, _))| blob_db == db)
+            .map(|(id, _)| *id)
+            .collect();
+        for id in ids {
+            if let Some((_, blob)) = blobs.remove(&id) {
+                if let Err(e) = blob.lock().await.close().await {
+                    log::error!("failed to close blob {id}: {e}");
+                }
+            }
+        }
+    }
+}
+
+/// A cursor that can be read by a `fetch_next` call without holding the
+/// lock on every other cursor.
+type SharedCursor = Arc<Mutex<Cursor>>;
+
+/// Cursors opened through the `select_stream` command, keyed by the id
+/// handed out to the frontend, along with the database they read from.
+#[derive(Default)]
+struct Cursors {
+    next_id: AtomicU32,
+    cursors: Mutex<HashMap<u32, (String, SharedCursor)>>,
+}
+
+impl Cursors {
+    async fn get(&self, id: u32) -> Result<SharedCursor> {
+        self.cursors
+            .lock()
+            .await
+            .get(&id)
+            .map(|(_, cursor)| cursor.clone())
+            .ok_or(Error::CursorNotFound(id))
+    }
+
+    async fn remove(&self, id: u32) -> Result<()> {
+        self.cursors
+            .lock()
+            .await
+            .remove(&id)
+            .map(|_| ())
+            .ok_or(Error::CursorNotFound(id))
+    }
+
+    /// Stops every open cursor of `db`.
+    async fn close_all(&self, db: &str) {
+        self.cursors
+            .lock()
+            .await
+            .retain(|_, (cursor_db, _)| cursor_db != db);
+    }
 }
 
+/// The migrations registered for each database, along with the version
+/// a database is migrated to when loaded, if it is not the latest one.
 #[derive(Default)]
-struct DbInstances(Mutex<HashMap<String, Pool<Db>>>);
+struct Migrations {
+    migrations: HashMap<String, MigrationList>,
+    targets: HashMap<String, i64>,
+}
+
+impl Migrations {
+    /// Brings a freshly loaded database to its target version, or applies
+    /// all of its migrations if no target was set.
+    async fn run(&self, db: &str, pool: &DbPool) -> Result<()> {
+        let Some(migrations) = self.migrations.get(db) else {
+            return Ok(());
+        };
+        match self.targets.get(db) {
+            Some(version) => self.migrate_to(db, pool, *version).await,
+            None => {
+                let migrator = Migrator::new(migrations.clone()).await?;
+                pool.migrate(&migrator).await
+            }
+        }
+    }
+
+    /// Applies the migrations up to `version` and reverts the applied ones
+    /// above it.
+    async fn migrate_to(&self, db: &str, pool: &DbPool, version: i64) -> Result<()> {
+        self.undo(db, pool, version).await?;
+
+        let migrations = self
+            .migrations
+            .get(db)
+            .ok_or_else(|| Error::MigrationsNotFound(db.to_string()))?;
+        let migrator = Migrator::new(migrations.up_to(version)).await?;
+        pool.migrate(&migrator).await
+    }
+
+    /// Reverts the last `steps` applied migrations.
+    async fn rollback(&self, db: &str, pool: &DbPool, steps: usize) -> Result<()> {
+        let target = pool
+            .applied_migrations()
+            .await?
+            .iter()
+            .rev()
+            .nth(steps)
+            .map(|applied| applied.version)
+            .unwrap_or(0);
+        self.undo(db, pool, target).await
+    }
+
+    /// Compares the registered migrations of `db` with the ones recorded as
+    /// applied in the database.
+    async fn status(
+        &self,
+        db: &str,
+        pool: &DbPool,
+        read_only: bool,
+    ) -> Result<Vec<MigrationStatus>> {
+        let Some(migrations) = self.migrations.get(db) else {
+            return Ok(Vec::new());
+        };
+        let migrator = Migrator::new(migrations.clone()).await?;
+        let records = pool.migration_records(read_only).await?;
 
-struct Migrations(Mutex<HashMap<String, MigrationList>>);
+        Ok(migrator
+            .iter()
+            .filter(|migration| !migration.migration_type.is_down_migration())
+            .map(|migration| {
+                let record = records
+                    .iter()
+                    .find(|record| record.version == migration.version && record.success);
+                MigrationStatus {
+                    version: migration.version,
+                    description: migration.description.to_string(),
+                    applied_at: record.map(|record| record.installed_on.clone()),
+                    checksum_matches: record
+                        .map(|record| *record.checksum == *migration.checksum)
+                        .unwrap_or(false),
+                    pending: record.is_none(),
+                }
+            })
+            .collect())
+    }
+
+    /// Reverts the applied migrations with a version above `target`.
+    async fn undo(&self, db: &str, pool: &DbPool, target: i64) -> Result<()> {
+        let migrations = self
+            .migrations
+            .get(db)
+            .ok_or_else(|| Error::MigrationsNotFound(db.to_string()))?;
+
+        // sqlx skips applied migrations without a down migration, which
+        // would leave the database above the requested version
+        for applied in pool.applied_migrations().await? {
+            if applied.version > target && !migrations.is_reversible(applied.version) {
+                return Err(Error::IrreversibleMigration(applied.version));
+            }
+        }
+
+        let migrator = Migrator::new(migrations.clone()).await?;
+        pool.undo(&migrator, target).await
+    }
+}
+
+/// The state of a registered migration in a database.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MigrationStatus {
+    pub version: i64,
+    pub description: String,
+    /// When the migration was applied, as reported by the database.
+    pub applied_at: Option<String>,
+    /// Whether the applied migration matches the registered SQL. Always
+    /// `false` for pending migrations.
+    pub checksum_matches: bool,
+    pub pending: bool,
+}
 
 #[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct PluginConfig {
     #[serde(default)]
     preload: Vec<String>,
+    /// Connection options, keyed by database.
+    #[serde(default)]
+    options: HashMap<String, ConnectionOptions>,
+    /// Allow SQLite paths outside of the app directory, absolute or going
+    /// up with `..`.
+    #[serde(default)]
+    allow_external_paths: bool,
+    /// The databases the frontend may load, all of them if unset.
+    #[serde(default)]
+    scope: Scope,
+    /// Reject raw SQL and blob I/O from the frontend, as well as reverting
+    /// migrations and changing SQLCipher keys, leaving only named queries.
+    #[serde(default)]
+    disable_raw_queries: bool,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum MigrationKind {
     Up,
     Down,
@@ -115,7 +546,10 @@ impl From<MigrationKind> for MigrationType {
 }
 
 /// A migration definition.
-#[derive(Debug)]
+///
+/// A migration of kind [`MigrationKind::Down`] reverts the
+/// [`MigrationKind::Up`] migration with the same version.
+#[derive(Debug, Clone)]
 pub struct Migration {
     pub version: i64,
     pub description: &'static str,
@@ -123,55 +557,119 @@ pub struct Migration {
     pub kind: MigrationKind,
 }
 
-#[derive(Debug)]
-struct MigrationList(Vec<Migration>);
+impl From<Migration> for SqlxMigration {
+    fn from(migration: Migration) -> Self {
+        SqlxMigration::new(
+            migration.version,
+            migration.description.into(),
+            migration.kind.into(),
+            migration.sql.into(),
+            false,
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct MigrationList(Vec<SqlxMigration>);
+
+impl MigrationList {
+    /// Adds migrations, keeping the list ordered by version.
+    fn extend(&mut self, migrations: impl IntoIterator<Item = SqlxMigration>) {
+        self.0.extend(migrations);
+        self.0.sort_by_key(|migration| migration.version);
+    }
+
+    /// Keeps only the migrations up to and including `version`.
+    fn up_to(&self, version: i64) -> Self {
+        Self(
+            self.0
+                .iter()
+                .filter(|migration| migration.version <= version)
+                .cloned()
+                .collect(),
+        )
+    }
+
+    fn is_reversible(&self, version: i64) -> bool {
+        self.0.iter().any(|migration| {
+            migration.version == version && migration.migration_type.is_down_migration()
+        })
+    }
+}
 
 impl MigrationSource<'static> for MigrationList {
     fn resolve(self) -> BoxFuture<'static, std::result::Result<Vec<SqlxMigration>, BoxDynError>> {
-        Box::pin(async move {
-            let mut migrations = Vec::new();
-            for migration in self.0 {
-                if matches!(migration.kind, MigrationKind::Up) {
-                    migrations.push(SqlxMigration::new(
-                        migration.version,
-                        migration.description.into(),
-                        migration.kind.into(),
-                        migration.sql.into(),
-                        false,
-                    ));
-                }
-            }
-            Ok(migrations)
-        })
+        Box::pin(async move { Ok(self.0) })
+    }
+}
+
+/// A directory of migration files, read when the plugin is set up.
+enum MigrationDir {
+    Path(PathBuf),
+    /// A path relative to the app's resource directory.
+    Resource(PathBuf),
+}
+
+impl MigrationDir {
+    /// Reads the migrations with sqlx's directory source, which parses
+    /// `<VERSION>_<DESCRIPTION>.up.sql` / `.down.sql` file names.
+    async fn resolve<R: Runtime>(self, app: &AppHandle<R>) -> Result<Vec<SqlxMigration>> {
+        let dir = match self {
+            Self::Path(dir) => dir,
+            Self::Resource(dir) => app.path_resolver().resolve_resource(&dir).ok_or_else(|| {
+                MigrateError::Source(
+                    format!("resource directory {} not found", dir.display()).into(),
+                )
+            })?,
+        };
+        Ok(dir.resolve().await.map_err(MigrateError::Source)?)
     }
 }
 
 #[command]
 async fn load<R: Runtime>(
-    #[allow(unused_variables)] app: AppHandle<R>,
+    app: AppHandle<R>,
     db_instances: State<'_, DbInstances>,
     migrations: State<'_, Migrations>,
+    config: State<'_, ConnectionConfig>,
     db: String,
+    options: Option<ConnectionOptions>,
 ) -> Result<String> {
-    #[cfg(feature = "sqlite")]
-    let fqdb = path_mapper(app_path(&app), &db);
-    #[cfg(not(feature = "sqlite"))]
-    let fqdb = db.clone();
+    if !config.scope.is_allowed(&db) {
+        return Err(Error::NotAllowed(db));
+    }
 
+    let options = options.unwrap_or_default();
+    // pragmas are run unquoted, so only the config may set them
     #[cfg(feature = "sqlite")]
-    create_dir_all(app_path(&app)).expect("Problem creating App directory!");
-
-    if !Db::database_exists(&fqdb).await.unwrap_or(false) {
-        Db::create_database(&fqdb).await?;
+    if options.has_pragmas() {
+        return Err(Error::Unsupported(
+            "pragmas can only be set in the plugin config".to_string(),
+        ));
     }
-    let pool = Pool::connect(&fqdb).await?;
 
-    if let Some(migrations) = migrations.0.lock().await.remove(&db) {
-        let migrator = Migrator::new(migrations).await?;
-        migrator.run(&pool).await?;
+    // options passed from the frontend take precedence over the config
+    let options = options.or(config.options.get(&db));
+    #[cfg(feature = "sqlite")]
+    let options = if config.is_read_only_alias(&app, &db, &options)? {
+        options.with_read_only()
+    } else {
+        options
+    };
+    // and keys set from Rust over both
+    #[cfg(feature = "sqlcipher")]
+    let options = options.with_key(app.state::<SqlCipherKeys>().get(&db));
+    let pool = DbPool::connect(&db, &app, &options, config.allow_external_paths).await?;
+
+    if !options.is_read_only() {
+        migrations.run(&db, &pool).await?;
     }
 
-    db_instances.0.lock().await.insert(db.clone(), pool);
+    let instance = DbInstance {
+        pool,
+        read_only: options.is_read_only(),
+    };
+    db_instances.0.write().await.insert(db.clone(), instance);
     Ok(db)
 }
 
@@ -179,144 +677,826 @@ async fn load<R: Runtime>(
 /// name is passed in then _all_ database connection pools will be
 /// shut down.
 #[command]
-async fn close(db_instances: State<'_, DbInstances>, db: Option<String>) -> Result<bool> {
-    let mut instances = db_instances.0.lock().await;
-
+async fn close(
+    db_instances: State<'_, DbInstances>,
+    transactions: State<'_, Transactions>,
+    cursors: State<'_, Cursors>,
+    blobs: State<'_, Blobs>,
+    db: Option<String>,
+) -> Result<bool> {
     let pools = if let Some(db) = db {
         vec![db]
     } else {
-        instances.keys().cloned().collect()
+        db_instances.0.read().await.keys().cloned().collect()
     };
 
     for pool in pools {
-        let db = instances
-            .get_mut(&pool) //
-            .ok_or(Error::DatabaseNotLoaded(pool))?;
-        db.close().await;
+        // open transactions, cursors and blobs hold on to a connection,
+        // which would keep the pool from closing
+        transactions.rollback_all(&pool).await;
+        cursors.close_all(&pool).await;
+        blobs.close_all(&pool).await;
+
+        db_instances.get(&pool).await?.close().await;
     }
 
     Ok(true)
 }
 
+/// Changes the SQLCipher key of an encrypted SQLite database, replacing
+/// its pool with one using the new key.
+#[command]
+async fn rekey(
+    db_instances: State<'_, DbInstances>,
+    queries: State<'_, Queries>,
+    db: String,
+    key: String,
+) -> Result<()> {
+    // a wrong key locks the app out of the database, like a raw query
+    // dropping it would
+    queries.check_raw()?;
+    db_instances.check_writable(&db).await?;
+    let pool = db_instances.get(&db).await?;
+    let rekeyed = pool.rekey(&key).await?;
+    if let Some(instance) = db_instances.0.write().await.get_mut(&db) {
+        instance.pool = rekeyed;
+    }
+    // connections still checked out of the old pool, by transactions,
+    // cursors or blobs, are closed once they are returned
+    spawn(async move { pool.close().await });
+    Ok(())
+}
+
+/// Starts a transaction on a dedicated connection of the database and
+/// returns its id, to be passed to `execute`, `select`, `commit` and
+/// `rollback`.
+#[command]
+async fn begin(
+    db_instances: State<'_, DbInstances>,
+    transactions: State<'_, Transactions>,
+    db: String,
+) -> Result<u32> {
+    // a query could end a transaction of a read-only database and change
+    // the read-only default of its session before the next one
+    db_instances.check_writable(&db).await?;
+    let transaction = db_instances.get(&db).await?.begin().await?;
+    Ok(transactions.insert(db, transaction).await)
+}
+
+#[command]
+async fn commit(transactions: State<'_, Transactions>, transaction_id: u32) -> Result<()> {
+    let transaction = transactions.take(transaction_id).await?;
+    transaction.commit().await
+}
+
+#[command]
+async fn rollback(transactions: State<'_, Transactions>, transaction_id: u32) -> Result<()> {
+    let transaction = transactions.take(transaction_id).await?;
+    transaction.rollback().await
+}
+
 /// Execute a command against the database
 #[command]
 async fn execute(
     db_instances: State<'_, DbInstances>,
+    transactions: State<'_, Transactions>,
+    queries: State<'_, Queries>,
     db: String,
     query: String,
-    values: Vec<JsonValue>,
+    values: BindValues,
+    transaction_id: Option<u32>,
 ) -> Result<(u64, LastInsertId)> {
-    let mut instances = db_instances.0.lock().await;
-
-    let db = instances.get_mut(&db).ok_or(Error::DatabaseNotLoaded(db))?;
-    let mut query = sqlx::query(&query);
-    for value in values {
-        if value.is_null() {
-            query = query.bind(None::<JsonValue>);
-        } else if value.is_string() {
-            query = query.bind(value.as_str().unwrap().to_owned())
-        } else {
-            query = query.bind(value);
-        }
+    queries.check_raw()?;
+    execute_query(
+        &db_instances,
+        &transactions,
+        &db,
+        &query,
+        values,
+        transaction_id,
+    )
+    .await
+}
+
+async fn execute_query(
+    db_instances: &DbInstances,
+    transactions: &Transactions,
+    db: &str,
+    query: &str,
+    values: BindValues,
+    transaction_id: Option<u32>,
+) -> Result<(u64, LastInsertId)> {
+    db_instances.check_writable(db).await?;
+
+    if let Some(id) = transaction_id {
+        let mut transaction = transactions.lock(db, id).await?;
+        let (query, values) = values.into_positional(query, transaction.placeholders())?;
+        return transaction.execute(&query, values).await;
     }
-    let result = query.execute(&*db).await?;
-    #[cfg(feature = "sqlite")]
-    let r = Ok((result.rows_affected(), result.last_insert_rowid()));
-    #[cfg(feature = "mysql")]
-    let r = Ok((result.rows_affected(), result.last_insert_id()));
-    #[cfg(feature = "postgres")]
-    let r = Ok((result.rows_affected(), 0));
-    r
+
+    let pool = db_instances.get(db).await?;
+    let (query, values) = values.into_positional(query, pool.placeholders())?;
+    pool.execute(&query, values).await
+}
+
+/// Execute a batch of statements in a single round trip. Unless they are
+/// part of an already open transaction, the statements run inside a
+/// transaction of their own, so either all of them land or none do.
+#[command]
+async fn execute_batch(
+    db_instances: State<'_, DbInstances>,
+    transactions: State<'_, Transactions>,
+    queries: State<'_, Queries>,
+    db: String,
+    statements: Vec<BatchStatement>,
+    transaction_id: Option<u32>,
+) -> Result<Vec<(u64, LastInsertId)>> {
+    queries.check_raw()?;
+    db_instances.check_writable(&db).await?;
+
+    if let Some(id) = transaction_id {
+        let mut transaction = transactions.lock(&db, id).await?;
+        return transaction.execute_batch(statements).await;
+    }
+
+    db_instances.get(&db).await?.execute_batch(statements).await
 }
 
 #[command]
+#[allow(clippy::too_many_arguments)]
 async fn select(
     db_instances: State<'_, DbInstances>,
+    transactions: State<'_, Transactions>,
+    queries: State<'_, Queries>,
     db: String,
     query: String,
-    values: Vec<JsonValue>,
+    values: BindValues,
+    transaction_id: Option<u32>,
+    big_numbers: Option<BigNumbers>,
+    binary: Option<Binary>,
 ) -> Result<Vec<HashMap<String, JsonValue>>> {
-    let mut instances = db_instances.0.lock().await;
-    let db = instances.get_mut(&db).ok_or(Error::DatabaseNotLoaded(db))?;
-    let mut query = sqlx::query(&query);
-    for value in values {
-        if value.is_null() {
-            query = query.bind(None::<JsonValue>);
-        } else if value.is_string() {
-            query = query.bind(value.as_str().unwrap().to_owned())
-        } else {
-            query = query.bind(value);
-        }
+    queries.check_raw()?;
+    select_query(
+        &db_instances,
+        &transactions,
+        &db,
+        &query,
+        values,
+        transaction_id,
+        decode_options(big_numbers, binary),
+    )
+    .await
+}
+
+async fn select_query(
+    db_instances: &DbInstances,
+    transactions: &Transactions,
+    db: &str,
+    query: &str,
+    values: BindValues,
+    transaction_id: Option<u32>,
+    decode: DecodeOptions,
+) -> Result<Vec<HashMap<String, JsonValue>>> {
+    if let Some(id) = transaction_id {
+        let mut transaction = transactions.lock(db, id).await?;
+        let (query, values) = values.into_positional(query, transaction.placeholders())?;
+        return transaction.select(&query, values, decode).await;
     }
-    let rows = query.fetch_all(&*db).await?;
-    let mut values = Vec::new();
-    for row in rows {
-        let mut value = HashMap::default();
-        for (i, column) in row.columns().iter().enumerate() {
-            let v = row.try_get_raw(i)?;
 
-            let v = crate::decode::to_json(v)?;
+    if let Some(mut transaction) = db_instances.read_only_transaction(db).await? {
+        let (query, values) = values.into_positional(query, transaction.placeholders())?;
+        let rows = transaction.select(&query, values, decode).await?;
+        transaction.rollback().await?;
+        return Ok(rows);
+    }
 
-            value.insert(column.name().to_string(), v);
+    let pool = db_instances.get(db).await?;
+    let (query, values) = values.into_positional(query, pool.placeholders())?;
+    pool.select(&query, values, decode).await
+}
+
+fn decode_options(big_numbers: Option<BigNumbers>, binary: Option<Binary>) -> DecodeOptions {
+    DecodeOptions {
+        big_numbers: big_numbers.unwrap_or_default(),
+        binary: binary.unwrap_or_default(),
+    }
+}
+
+/// Reads a single binary value, the first column of the first row of the
+/// query, without converting it to JSON. Returns it base64 encoded, or
+/// `None` if there is no row or the value is `NULL`.
+#[command]
+async fn read_blob(
+    db_instances: State<'_, DbInstances>,
+    transactions: State<'_, Transactions>,
+    queries: State<'_, Queries>,
+    db: String,
+    query: String,
+    values: BindValues,
+    transaction_id: Option<u32>,
+) -> Result<Option<String>> {
+    queries.check_raw()?;
+    let blob = if let Some(id) = transaction_id {
+        let mut transaction = transactions.lock(&db, id).await?;
+        let (query, values) = values.into_positional(&query, transaction.placeholders())?;
+        transaction.read_blob(&query, values).await?
+    } else if let Some(mut transaction) = db_instances.read_only_transaction(&db).await? {
+        let (query, values) = values.into_positional(&query, transaction.placeholders())?;
+        let blob = transaction.read_blob(&query, values).await?;
+        transaction.rollback().await?;
+        blob
+    } else {
+        let pool = db_instances.get(&db).await?;
+        let (query, values) = values.into_positional(&query, pool.placeholders())?;
+        pool.read_blob(&query, values).await?
+    };
+    Ok(blob.map(|blob| STANDARD.encode(blob)))
+}
+
+/// Writes a single binary value, sent base64 encoded, by executing a
+/// statement with `data` bound to its first placeholder, followed by
+/// `values`, or to `:data` when the values are named.
+#[command]
+#[allow(clippy::too_many_arguments)]
+async fn write_blob(
+    db_instances: State<'_, DbInstances>,
+    transactions: State<'_, Transactions>,
+    queries: State<'_, Queries>,
+    db: String,
+    query: String,
+    data: String,
+    values: Option<BindValues>,
+    transaction_id: Option<u32>,
+) -> Result<(u64, LastInsertId)> {
+    queries.check_raw()?;
+    let blob = serde_json::json!({ "type": "blob", "value": data });
+    let values = match values.unwrap_or_default() {
+        BindValues::Positional(mut values) => {
+            values.insert(0, blob);
+            BindValues::Positional(values)
         }
+        BindValues::Named(mut values) => {
+            values.insert("data".to_string(), blob);
+            BindValues::Named(values)
+        }
+    };
+    execute_query(
+        &db_instances,
+        &transactions,
+        &db,
+        &query,
+        values,
+        transaction_id,
+    )
+    .await
+}
+
+/// Whether a named query is run like `execute` or like `select`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum NamedQueryKind {
+    Execute,
+    #[default]
+    Select,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum NamedQueryResult {
+    Execute((u64, LastInsertId)),
+    Select(Vec<HashMap<String, JsonValue>>),
+}
+
+/// Runs a query registered with [`Builder::add_named_query`], the frontend
+/// only supplying its name and bind values.
+#[command]
+#[allow(clippy::too_many_arguments)]
+async fn run_named(
+    db_instances: State<'_, DbInstances>,
+    transactions: State<'_, Transactions>,
+    queries: State<'_, Queries>,
+    db: String,
+    name: String,
+    values: BindValues,
+    kind: Option<NamedQueryKind>,
+    transaction_id: Option<u32>,
+    big_numbers: Option<BigNumbers>,
+    binary: Option<Binary>,
+) -> Result<NamedQueryResult> {
+    let query = queries.named(&db, &name)?;
+    match kind.unwrap_or_default() {
+        NamedQueryKind::Execute => execute_query(
+            &db_instances,
+            &transactions,
+            &db,
+            query,
+            values,
+            transaction_id,
+        )
+        .await
+        .map(NamedQueryResult::Execute),
+        NamedQueryKind::Select => select_query(
+            &db_instances,
+            &transactions,
+            &db,
+            query,
+            values,
+            transaction_id,
+            decode_options(big_numbers, binary),
+        )
+        .await
+        .map(NamedQueryResult::Select),
+    }
+}
+
+/// Like `select`, but returns the rows as arrays of values along with the
+/// name, type and nullability of each column, which keeps the column order
+/// and duplicated column names.
+#[command]
+#[allow(clippy::too_many_arguments)]
+async fn select_columns(
+    db_instances: State<'_, DbInstances>,
+    transactions: State<'_, Transactions>,
+    queries: State<'_, Queries>,
+    db: String,
+    query: String,
+    values: BindValues,
+    transaction_id: Option<u32>,
+    big_numbers: Option<BigNumbers>,
+    binary: Option<Binary>,
+) -> Result<ColumnarRows> {
+    queries.check_raw()?;
+    if let Some(id) = transaction_id {
+        let mut transaction = transactions.lock(&db, id).await?;
+        let (query, values) = values.into_positional(&query, transaction.placeholders())?;
+        return transaction
+            .select_columns(&query, values, decode_options(big_numbers, binary))
+            .await;
+    }
+
+    if let Some(mut transaction) = db_instances.read_only_transaction(&db).await? {
+        let (query, values) = values.into_positional(&query, transaction.placeholders())?;
+        let rows = transaction
+            .select_columns(&query, values, decode_options(big_numbers, binary))
+            .await?;
+        transaction.rollback().await?;
+        return Ok(rows);
+    }
+
+    let pool = db_instances.get(&db).await?;
+    let (query, values) = values.into_positional(&query, pool.placeholders())?;
+    pool.select_columns(&query, values, decode_options(big_numbers, binary))
+        .await
+}
+
+/// Starts a query whose rows are fetched incrementally instead of all at
+/// once, and returns the id of a cursor to read them with `fetch_next`.
+#[command]
+#[allow(clippy::too_many_arguments)]
+async fn select_stream(
+    db_instances: State<'_, DbInstances>,
+    cursors: State<'_, Cursors>,
+    queries: State<'_, Queries>,
+    db: String,
+    query: String,
+    values: BindValues,
+    chunk_size: Option<usize>,
+    big_numbers: Option<BigNumbers>,
+    binary: Option<Binary>,
+) -> Result<u32> {
+    queries.check_raw()?;
+    let chunk_size = chunk_size.unwrap_or(DEFAULT_CHUNK_SIZE);
+    if chunk_size == 0 {
+        return Err(Error::InvalidValue(
+            "chunk_size".to_string(),
+            chunk_size.to_string(),
+        ));
+    }
+
+    let decode = decode_options(big_numbers, binary);
+    let cursor = if let Some(transaction) = db_instances.read_only_transaction(&db).await? {
+        let (query, values) = values.into_positional(&query, transaction.placeholders())?;
+        transaction.stream(query.into_owned(), values, decode, chunk_size)
+    } else {
+        let pool = db_instances.get(&db).await?;
+        let (query, values) = values.into_positional(&query, pool.placeholders())?;
+        pool.stream(query.into_owned(), values, decode, chunk_size)
+    };
+
+    let id = cursors.next_id.fetch_add(1, Ordering::Relaxed);
+    cursors
+        .cursors
+        .lock()
+        .await
+        .insert(id, (db, Arc::new(Mutex::new(cursor))));
+    Ok(id)
+}
+
+/// Returns the next chunk of rows of a cursor, or `None` once all rows
+/// have been read, at which point the cursor is closed.
+#[command]
+async fn fetch_next(
+    cursors: State<'_, Cursors>,
+    cursor_id: u32,
+) -> Result<Option<Vec<HashMap<String, JsonValue>>>> {
+    let cursor = cursors.get(cursor_id).await?;
+    let rows = cursor.lock().await.next().await;
+    if !matches!(rows, Ok(Some(_))) {
+        let _ = cursors.remove(cursor_id).await;
+    }
+    rows
+}
+
+/// Stops a cursor before all of its rows have been read.
+#[command]
+async fn close_cursor(cursors: State<'_, Cursors>, cursor_id: u32) -> Result<()> {
+    cursors.remove(cursor_id).await
+}
+
+#[derive(Serialize)]
+struct BlobInfo {
+    id: u32,
+    size: usize,
+}
+
+/// Opens the value of `column` in the row `row_id` of `table` for
+/// incremental I/O, SQLite only. Returns the id of the blob, to read and
+/// write byte ranges of it with `read_blob_range` and `write_blob_range`,
+/// along with its size in bytes.
+#[command]
+#[allow(clippy::too_many_arguments)]
+async fn open_blob(
+    db_instances: State<'_, DbInstances>,
+    blobs: State<'_, Blobs>,
+    queries: State<'_, Queries>,
+    db: String,
+    table: String,
+    column: String,
+    row_id: i64,
+    read_only: Option<bool>,
+) -> Result<BlobInfo> {
+    queries.check_raw()?;
+    let writable = !read_only.unwrap_or_default();
+    if writable {
+        db_instances.check_writable(&db).await?;
+    }
+
+    let pool = db_instances.get(&db).await?;
+    let blob = pool.open_blob(&table, &column, row_id, writable).await?;
+    let size = blob.size();
+
+    let id = blobs.next_id.fetch_add(1, Ordering::Relaxed);
+    blobs
+        .blobs
+        .lock()
+        .await
+        .insert(id, (db, Arc::new(Mutex::new(blob))));
+    Ok(BlobInfo { id, size })
+}
+
+/// Reads `length` bytes of a blob from `offset`, or up to its end, and
+/// returns them base64 encoded.
+#[command]
+async fn read_blob_range(
+    blobs: State<'_, Blobs>,
+    blob_id: u32,
+    offset: usize,
+    length: Option<usize>,
+) -> Result<String> {
+    let blob = blobs.get(blob_id).await?;
+    let mut blob = blob.lock().await;
+    let length = length.unwrap_or_else(|| blob.size().saturating_sub(offset));
+    let data = blob.read(offset, length).await?;
+    Ok(STANDARD.encode(data))
+}
+
+/// Writes base64 encoded bytes to a blob from `offset`. The size of the
+/// blob cannot change, so the bytes must fit in it.
+#[command]
+async fn write_blob_range(
+    blobs: State<'_, Blobs>,
+    blob_id: u32,
+    offset: usize,
+    data: String,
+) -> Result<()> {
+    let data = STANDARD
+        .decode(data)
+        .map_err(|e| Error::InvalidValue("blob".to_string(), e.to_string()))?;
+    let blob = blobs.get(blob_id).await?;
+    let mut blob = blob.lock().await;
+    blob.write(offset, &data).await
+}
+
+/// Closes a blob and returns its connection to the pool.
+#[command]
+async fn close_blob(blobs: State<'_, Blobs>, blob_id: u32) -> Result<()> {
+    let blob = blobs.remove(blob_id).await?;
+    let mut blob = blob.lock().await;
+    blob.close().await
+}
+
+/// Migrates a loaded database up or down to the given version, reverting
+/// applied migrations above it with their down migrations.
+#[command]
+async fn migrate_to(
+    db_instances: State<'_, DbInstances>,
+    migrations: State<'_, Migrations>,
+    queries: State<'_, Queries>,
+    db: String,
+    version: i64,
+) -> Result<()> {
+    // reverting migrations can drop data, like a raw query could
+    queries.check_raw()?;
+    db_instances.check_writable(&db).await?;
+    let pool = db_instances.get(&db).await?;
+    migrations.migrate_to(&db, &pool, version).await
+}
+
+/// Reverts the last `steps` applied migrations of a loaded database.
+#[command]
+async fn rollback_migrations(
+    db_instances: State<'_, DbInstances>,
+    migrations: State<'_, Migrations>,
+    queries: State<'_, Queries>,
+    db: String,
+    steps: usize,
+) -> Result<()> {
+    queries.check_raw()?;
+    db_instances.check_writable(&db).await?;
+    let pool = db_instances.get(&db).await?;
+    migrations.rollback(&db, &pool, steps).await
+}
+
+/// Lists the registered migrations of a loaded database, along with
+/// whether and when they were applied.
+#[command]
+async fn migration_status(
+    db_instances: State<'_, DbInstances>,
+    migrations: State<'_, Migrations>,
+    db: String,
+) -> Result<Vec<MigrationStatus>> {
+    let pool = db_instances.get(&db).await?;
+    let read_only = db_instances.is_read_only(&db).await?;
+    migrations.status(&db, &pool, read_only).await
+}
+
+/// Lists the migrations registered with [`Builder::add_migrations`] for a
+/// loaded database, along with whether and when they were applied.
+pub async fn get_migration_status<R: Runtime, M: Manager<R>>(
+    manager: &M,
+    db: &str,
+) -> Result<Vec<MigrationStatus>> {
+    let instances = manager.state::<DbInstances>();
+    let pool = instances.get(db).await?;
+    let read_only = instances.is_read_only(db).await?;
+    manager
+        .state::<Migrations>()
+        .status(db, &pool, read_only)
+        .await
+}
+
+/// Extension trait giving Rust code access to the databases loaded by the
+/// plugin, either through `Database.load` in the frontend or preloaded
+/// from the config.
+///
+/// # Example
+///
+/// ```ignore
+/// use tauri_plugin_sql::{DbPool, DecodeOptions, SqlExt};
+///
+/// let db = app.db_pool("sqlite:test.db").await?;
+/// let todos = db
+///     .select("SELECT * FROM todos WHERE done = $1", vec![false.into()], DecodeOptions::default())
+///     .await?;
+///
+/// // or with sqlx directly
+/// if let DbPool::Sqlite(pool) = &db {
+///     let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM todos")
+///         .fetch_one(pool)
+///         .await?;
+/// }
+/// ```
+pub trait SqlExt<R: Runtime> {
+    /// Returns a handle to the pool of a loaded database, which shares its
+    /// connections with the frontend.
+    fn db_pool(&self, db: &str) -> impl Future<Output = Result<DbPool>> + Send;
+
+    /// Migrates a loaded database up or down to `version`, like the
+    /// `migrateTo` call of the frontend, reverting the applied migrations
+    /// above it with their [`MigrationKind::Down`] migration.
+    fn migrate_to(&self, db: &str, version: i64) -> impl Future<Output = Result<()>> + Send;
+
+    /// Reverts the last `steps` applied migrations of a loaded database.
+    fn rollback_migrations(
+        &self,
+        db: &str,
+        steps: usize,
+    ) -> impl Future<Output = Result<()>> + Send;
+}
 
-        values.push(value);
+impl<R: Runtime, M: Manager<R>> SqlExt<R> for M {
+    fn db_pool(&self, db: &str) -> impl Future<Output = Result<DbPool>> + Send {
+        let instances = self.state::<DbInstances>().inner();
+        let db = db.to_string();
+        async move { instances.get(&db).await }
     }
 
-    Ok(values)
+    fn migrate_to(&self, db: &str, version: i64) -> impl Future<Output = Result<()>> + Send {
+        let instances = self.state::<DbInstances>().inner();
+        let migrations = self.state::<Migrations>().inner();
+        let db = db.to_string();
+        async move {
+            instances.check_writable(&db).await?;
+            let pool = instances.get(&db).await?;
+            migrations.migrate_to(&db, &pool, version).await
+        }
+    }
+
+    fn rollback_migrations(
+        &self,
+        db: &str,
+        steps: usize,
+    ) -> impl Future<Output = Result<()>> + Send {
+        let instances = self.state::<DbInstances>().inner();
+        let migrations = self.state::<Migrations>().inner();
+        let db = db.to_string();
+        async move {
+            instances.check_writable(&db).await?;
+            let pool = instances.get(&db).await?;
+            migrations.rollback(&db, &pool, steps).await
+        }
+    }
 }
 
 /// Tauri SQL plugin builder.
 #[derive(Default)]
 pub struct Builder {
     migrations: Option<HashMap<String, MigrationList>>,
+    migration_dirs: Vec<(String, MigrationDir)>,
+    migration_targets: HashMap<String, i64>,
+    named_queries: HashMap<String, HashMap<String, String>>,
+    #[cfg(feature = "sqlcipher")]
+    sqlcipher_keys: SqlCipherKeys,
 }
 
 impl Builder {
     /// Add migrations to a database.
     #[must_use]
     pub fn add_migrations(mut self, db_url: &str, migrations: Vec<Migration>) -> Self {
-        self.migrations
-            .get_or_insert(Default::default())
-            .insert(db_url.to_string(), MigrationList(migrations));
+        self.migrations.get_or_insert(Default::default()).insert(
+            db_url.to_string(),
+            MigrationList(migrations.into_iter().map(Into::into).collect()),
+        );
+        self
+    }
+
+    /// Add the migrations stored as files in a directory to a database.
+    ///
+    /// Files follow the naming rules of `sqlx migrate`:
+    /// `<VERSION>_<DESCRIPTION>.up.sql` and `<VERSION>_<DESCRIPTION>.down.sql`
+    /// for reversible migrations, or `<VERSION>_<DESCRIPTION>.sql` for
+    /// migrations that cannot be reverted. The directory is read when the
+    /// plugin is set up.
+    #[must_use]
+    pub fn add_migrations_from_dir(mut self, db_url: &str, dir: impl Into<PathBuf>) -> Self {
+        self.migration_dirs
+            .push((db_url.to_string(), MigrationDir::Path(dir.into())));
+        self
+    }
+
+    /// Like [`Builder::add_migrations_from_dir`], with `dir` relative to the
+    /// app's resource directory. The directory must be bundled with the app
+    /// through `tauri > bundle > resources` in `tauri.conf.json`.
+    #[must_use]
+    pub fn add_migrations_from_resources(mut self, db_url: &str, dir: impl Into<PathBuf>) -> Self {
+        self.migration_dirs
+            .push((db_url.to_string(), MigrationDir::Resource(dir.into())));
+        self
+    }
+
+    /// Migrate a database to the given version when it is loaded, instead
+    /// of the latest one. Applied migrations above `version` are reverted
+    /// with their [`MigrationKind::Down`] migration, which makes it
+    /// possible to roll back a faulty schema release.
+    #[must_use]
+    pub fn migrate_to(mut self, db_url: &str, version: i64) -> Self {
+        self.migration_targets.insert(db_url.to_string(), version);
+        self
+    }
+
+    /// Register a query the frontend can run by name with `run_named`,
+    /// without sending any SQL. Together with `disableRawQueries` in the
+    /// plugin config, it limits the frontend to the queries of the app.
+    #[must_use]
+    pub fn add_named_query(mut self, db_url: &str, name: &str, sql: impl Into<String>) -> Self {
+        self.named_queries
+            .entry(db_url.to_string())
+            .or_default()
+            .insert(name.to_string(), sql.into());
+        self
+    }
+
+    /// Set the SQLCipher key of a database, which is then encrypted at rest.
+    /// Keys set from Rust take precedence over the ones passed to `load`.
+    #[cfg(feature = "sqlcipher")]
+    #[must_use]
+    pub fn sqlcipher_key(mut self, db_url: &str, key: impl Into<String>) -> Self {
+        self.sqlcipher_keys
+            .keys
+            .insert(db_url.to_string(), key.into());
+        self
+    }
+
+    /// Set a callback providing the SQLCipher key of the databases without
+    /// a key set with [`Builder::sqlcipher_key`], e.g. from the keychain of
+    /// the OS. It is called every time a database is loaded.
+    #[cfg(feature = "sqlcipher")]
+    #[must_use]
+    pub fn sqlcipher_key_provider<F>(mut self, provider: F) -> Self
+    where
+        F: Fn(&str) -> Option<String> + Send + Sync + 'static,
+    {
+        self.sqlcipher_keys.provider = Some(Arc::new(provider));
         self
     }
 
     pub fn build<R: Runtime>(mut self) -> TauriPlugin<R, Option<PluginConfig>> {
         PluginBuilder::new("sql")
-            .invoke_handler(tauri::generate_handler![load, execute, select, close])
+            .invoke_handler(tauri::generate_handler![
+                load,
+                execute,
+                execute_batch,
+                select,
+                select_columns,
+                close,
+                begin,
+                commit,
+                rollback,
+                select_stream,
+                fetch_next,
+                close_cursor,
+                migrate_to,
+                rollback_migrations,
+                migration_status,
+                rekey,
+                run_named,
+                read_blob,
+                write_blob,
+                open_blob,
+                read_blob_range,
+                write_blob_range,
+                close_blob
+            ])
             .setup_with_config(|app, config: Option<PluginConfig>| {
                 let config = config.unwrap_or_default();
 
-                #[cfg(feature = "sqlite")]
-                create_dir_all(app_path(app)).expect("problems creating App directory!");
-
                 tauri::async_runtime::block_on(async move {
+                    let mut migrations = self.migrations.take().unwrap_or_default();
+                    for (db, dir) in self.migration_dirs {
+                        let resolved = dir.resolve(app).await?;
+                        migrations.entry(db).or_default().extend(resolved);
+                    }
+                    let migrations = Migrations {
+                        migrations,
+                        targets: self.migration_targets,
+                    };
+
                     let instances = DbInstances::default();
-                    let mut lock = instances.0.lock().await;
+                    let mut lock = instances.0.write().await;
                     for db in config.preload {
-                        #[cfg(feature = "sqlite")]
-                        let fqdb = path_mapper(app_path(app), &db);
-                        #[cfg(not(feature = "sqlite"))]
-                        let fqdb = db.clone();
-
-                        if !Db::database_exists(&fqdb).await.unwrap_or(false) {
-                            Db::create_database(&fqdb).await?;
-                        }
-                        let pool = Pool::connect(&fqdb).await?;
+                        let options = config.options.get(&db).cloned().unwrap_or_default();
+                        #[cfg(feature = "sqlcipher")]
+                        let options = options.with_key(self.sqlcipher_keys.get(&db));
+                        let pool = DbPool::connect(&db, app, &options, config.allow_external_paths)
+                            .await?;
 
-                        if let Some(migrations) = self.migrations.as_mut().unwrap().remove(&db) {
-                            let migrator = Migrator::new(migrations).await?;
-                            migrator.run(&pool).await?;
+                        if !options.is_read_only() {
+                            migrations.run(&db, &pool).await?;
                         }
-                        lock.insert(db, pool);
+                        let instance = DbInstance {
+                            pool,
+                            read_only: options.is_read_only(),
+                        };
+                        lock.insert(db, instance);
                     }
                     drop(lock);
 
                     app.manage(instances);
-                    app.manage(Migrations(Mutex::new(
-                        self.migrations.take().unwrap_or_default(),
-                    )));
+                    app.manage(Transactions::default());
+                    app.manage(Cursors::default());
+                    app.manage(Blobs::default());
+                    app.manage(migrations);
+                    app.manage(Queries {
+                        named: self.named_queries,
+                        raw_disabled: config.disable_raw_queries,
+                    });
+                    app.manage(ConnectionConfig {
+                        options: config.options,
+                        allow_external_paths: config.allow_external_paths,
+                        scope: config.scope,
+                    });
+                    #[cfg(feature = "sqlcipher")]
+                    app.manage(self.sqlcipher_keys);
 
                     Ok(())
                 })
@@ -324,10 +1504,20 @@ impl Builder {
             .on_event(|app, event| {
                 if let RunEvent::Exit = event {
                     tauri::async_runtime::block_on(async move {
+                        let transactions = &*app.state::<Transactions>();
+                        transactions.transactions.lock().await.clear();
+                        let cursors = &*app.state::<Cursors>();
+                        cursors.cursors.lock().await.clear();
+
+                        let blobs = &*app.state::<Blobs>();
+
                         let instances = &*app.state::<DbInstances>();
-                        let instances = instances.0.lock().await;
-                        for value in instances.values() {
-                            value.close().await;
+                        let instances = instances.0.read().await;
+                        for (db, value) in instances.iter() {
+                            // open blobs hold on to a connection, which would
+                            // keep the pool from closing
+                            blobs.close_all(db).await;
+                            value.pool.close().await;
                         }
                     });
                 }
@@ -0,0 +1,89 @@
+// Copyright 2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use serde::Deserialize;
+
+/// The databases the frontend may load, as patterns matched against the
+/// connection string passed to `load`. Every database is allowed when no
+/// scope is configured.
+///
+/// In patterns, `*` matches any sequence of characters but `/`, `**` any
+/// sequence of characters and `?` a single character, so that
+/// `sqlite:data/*.db` allows the SQLite files of the `data` directory.
+#[derive(Debug, Default, Deserialize)]
+#[serde(transparent)]
+pub(crate) struct Scope(Option<Vec<String>>);
+
+impl Scope {
+    pub(crate) fn is_allowed(&self, db: &str) -> bool {
+        match &self.0 {
+            Some(patterns) => patterns.iter().any(|pattern| glob_match(pattern, db)),
+            None => true,
+        }
+    }
+}
+
+fn glob_match(pattern: &str, value: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let value: Vec<char> = value.chars().collect();
+    matches(&pattern, &value)
+}
+
+fn matches(pattern: &[char], value: &[char]) -> bool {
+    match pattern {
+        [] => value.is_empty(),
+        ['*', '*', rest @ ..] => (0..=value.len()).any(|i| matches(rest, &value[i..])),
+        ['*', rest @ ..] => (0..=value.len())
+            .take_while(|&i| i == 0 || value[i - 1] != '/')
+            .any(|i| matches(rest, &value[i..])),
+        ['?', rest @ ..] => !value.is_empty() && matches(rest, &value[1..]),
+        [c, rest @ ..] => value.first() == Some(c) && matches(rest, &value[1..]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn single_star_stops_at_slashes() {
+        assert!(glob_match("sqlite:data/*.db", "sqlite:data/test.db"));
+        assert!(glob_match("sqlite:data/*.db", "sqlite:data/.db"));
+        assert!(!glob_match("sqlite:data/*.db", "sqlite:data/dbs/test.db"));
+        assert!(!glob_match("sqlite:*", "sqlite:data/test.db"));
+    }
+
+    #[test]
+    fn double_star_crosses_slashes() {
+        assert!(glob_match("sqlite:data/**.db", "sqlite:data/test.db"));
+        assert!(glob_match("sqlite:data/**.db", "sqlite:data/dbs/test.db"));
+        assert!(glob_match("sqlite:**", "sqlite:data/test.db"));
+        assert!(!glob_match("sqlite:data/**.db", "sqlite:other/test.db"));
+    }
+
+    #[test]
+    fn question_mark_matches_one_character() {
+        assert!(glob_match("sqlite:test?.db", "sqlite:test1.db"));
+        assert!(!glob_match("sqlite:test?.db", "sqlite:test.db"));
+        assert!(!glob_match("sqlite:test?.db", "sqlite:test12.db"));
+    }
+
+    #[test]
+    fn literal_patterns_match_exactly() {
+        assert!(glob_match(
+            "postgres://localhost/app",
+            "postgres://localhost/app"
+        ));
+        assert!(!glob_match(
+            "postgres://localhost/app",
+            "postgres://localhost/app2"
+        ));
+    }
+
+    #[test]
+    fn missing_scope_allows_everything() {
+        assert!(Scope::default().is_allowed("sqlite:test.db"));
+        assert!(!Scope(Some(vec![])).is_allowed("sqlite:test.db"));
+    }
+}
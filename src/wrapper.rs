@@ -0,0 +1,1698 @@
+// Copyright 2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{collections::HashMap, future::poll_fn, time::Duration};
+
+#[cfg(any(feature = "sqlite", feature = "postgres"))]
+use std::str::FromStr;
+#[cfg(feature = "sqlite")]
+use std::{
+    collections::BTreeMap,
+    fs::create_dir_all,
+    path::{Component, Path, PathBuf},
+};
+
+use futures_core::stream::BoxStream;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use sqlx::{
+    migrate::{AppliedMigration, Migrate, MigrateDatabase, Migrator},
+    pool::PoolOptions,
+    Column, ColumnIndex, Database, Encode, Executor, IntoArguments, Pool, Row, Transaction, Type,
+    TypeInfo,
+};
+#[cfg(feature = "sqlite")]
+use tauri::{
+    api::path::{resolve_path, BaseDirectory},
+    Manager,
+};
+use tauri::{
+    async_runtime::{spawn, JoinHandle},
+    AppHandle, Runtime,
+};
+use time::{Date, OffsetDateTime, PrimitiveDateTime, Time};
+use tokio::sync::mpsc;
+
+#[cfg(feature = "mysql")]
+use sqlx::mysql::{MySql, MySqlQueryResult};
+#[cfg(feature = "postgres")]
+use sqlx::postgres::{PgConnectOptions, PgQueryResult, Postgres};
+#[cfg(feature = "sqlite")]
+use sqlx::sqlite::{
+    Sqlite, SqliteConnectOptions, SqliteJournalMode, SqliteQueryResult, SqliteSynchronous,
+};
+#[cfg(feature = "sqlcipher")]
+use sqlx::{ConnectOptions, Connection};
+
+#[cfg(feature = "sqlite")]
+use crate::blob::SqliteBlob;
+use crate::{
+    bind::{bind_values, BindExact, BindValues, Placeholders},
+    blob::DbBlob,
+    decode::DecodeOptions,
+    Error, Result,
+};
+
+#[cfg(feature = "sqlite")]
+/// Resolves the App's **file path** from the `AppHandle` context
+/// object, in the given base directory
+pub(crate) fn app_path<R: Runtime>(app: &AppHandle<R>, base_dir: BaseDir) -> Result<PathBuf> {
+    let dir = match base_dir {
+        BaseDir::App => {
+            #[allow(deprecated)] // FIXME: Change to non-deprecated function in Tauri v2
+            return app
+                .path_resolver()
+                .app_dir()
+                .ok_or_else(|| Error::PathResolution("no app directory was found".to_string()));
+        }
+        BaseDir::AppData => BaseDirectory::AppData,
+        BaseDir::AppLocalData => BaseDirectory::AppLocalData,
+        BaseDir::AppCache => BaseDirectory::AppCache,
+    };
+    resolve_path(&app.config(), app.package_info(), &app.env(), "", Some(dir))
+        .map_err(|e| Error::PathResolution(e.to_string()))
+}
+
+#[cfg(feature = "sqlite")]
+/// Whether the connection string is the one of an in-memory database,
+/// `sqlite::memory:` or a URI with `mode=memory` such as
+/// `file:name?mode=memory&cache=shared`.
+fn is_in_memory(connection_string: &str) -> bool {
+    let url = connection_string
+        .strip_prefix("sqlite:")
+        .unwrap_or(connection_string);
+    let (database, params) = url.split_once('?').unwrap_or((url, ""));
+    database == ":memory:" || params.split('&').any(|param| param == "mode=memory")
+}
+
+/// The directory relative SQLite paths are resolved against.
+#[cfg(feature = "sqlite")]
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum BaseDir {
+    /// The deprecated app directory of Tauri v1, kept as the default for
+    /// compatibility with existing databases.
+    #[default]
+    App,
+    AppData,
+    AppLocalData,
+    AppCache,
+}
+
+#[cfg(feature = "sqlite")]
+/// Maps the user supplied DB connection string to a connection string
+/// with a fully qualified file path to the App's designed "app_path"
+///
+/// Paths leaving `app_path`, absolute or going up with `..`, are rejected
+/// unless `allow_external_paths` is set.
+fn path_mapper(
+    mut app_path: PathBuf,
+    connection_string: &str,
+    allow_external_paths: bool,
+) -> Result<String> {
+    let (_, path) = connection_string
+        .split_once(':')
+        .ok_or_else(|| Error::InvalidDbUrl(connection_string.to_string()))?;
+
+    let is_external = Path::new(path).components().any(|component| {
+        matches!(
+            component,
+            Component::Prefix(_) | Component::RootDir | Component::ParentDir
+        )
+    });
+    if is_external && !allow_external_paths {
+        return Err(Error::PathResolution(format!(
+            "{path} is outside of the app directory"
+        )));
+    }
+    app_path.push(path);
+
+    app_path
+        .to_str()
+        .map(|path| format!("sqlite:{path}"))
+        .ok_or_else(|| Error::PathResolution(format!("{} is not valid UTF-8", app_path.display())))
+}
+
+#[cfg(feature = "sqlite")]
+/// The file a SQLite connection string opens, given the directory the app
+/// path of [`path_mapper`] resolves to. Equivalent spellings of a path,
+/// such as `sqlite:./test.db` and `sqlite:test.db`, resolve to the same
+/// file, as do symbolic links to an existing file.
+fn database_file(
+    app_path: PathBuf,
+    connection_string: &str,
+    allow_external_paths: bool,
+) -> Result<PathBuf> {
+    let url = path_mapper(app_path, connection_string, allow_external_paths)?;
+    let path = url.strip_prefix("sqlite:").unwrap_or(&url);
+    let path = path.split_once('?').map_or(path, |(path, _)| path);
+
+    let mut file = PathBuf::new();
+    for component in Path::new(path).components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                file.pop();
+            }
+            component => file.push(component),
+        }
+    }
+    Ok(std::fs::canonicalize(&file).unwrap_or(file))
+}
+
+/// The id of the last row inserted by an `execute` call.
+///
+/// Postgres has no equivalent, so its value is always `0`.
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(untagged)]
+pub enum LastInsertId {
+    #[cfg(feature = "sqlite")]
+    Sqlite(i64),
+    #[cfg(feature = "mysql")]
+    MySql(u64),
+    #[cfg(feature = "postgres")]
+    Postgres(u64),
+}
+
+/// A row of the `_sqlx_migrations` table sqlx keeps track of applied
+/// migrations in.
+pub(crate) struct MigrationRecord {
+    pub(crate) version: i64,
+    pub(crate) checksum: Vec<u8>,
+    pub(crate) installed_on: String,
+    pub(crate) success: bool,
+}
+
+/// The metadata of a column returned by `select_columns`.
+#[derive(Debug, Serialize)]
+pub(crate) struct ColumnInfo {
+    name: String,
+    /// The name of the column's type, as reported by the driver.
+    type_name: String,
+    /// Whether the column may contain `NULL`, if the driver knows.
+    nullable: Option<bool>,
+}
+
+/// The result of a `select_columns` call, with each row's values in the
+/// order of `columns`.
+#[derive(Debug, Serialize)]
+pub(crate) struct ColumnarRows {
+    columns: Vec<ColumnInfo>,
+    rows: Vec<Vec<JsonValue>>,
+}
+
+/// Options of the connection pool of a database, set in the plugin config
+/// or passed to `load`. Options left unset use the defaults of sqlx.
+#[derive(Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ConnectionOptions {
+    max_connections: Option<u32>,
+    min_connections: Option<u32>,
+    /// Seconds to wait for a connection before giving up.
+    acquire_timeout: Option<u64>,
+    /// Seconds after which an idle connection is closed.
+    idle_timeout: Option<u64>,
+    test_before_acquire: Option<bool>,
+    /// Whether the database is opened read-only, and the commands writing
+    /// to it rejected. With Postgres and MySQL, queries then run in
+    /// read-only transactions, as the session default could be changed.
+    read_only: Option<bool>,
+    /// Options of SQLite connections.
+    #[cfg(feature = "sqlite")]
+    sqlite: Option<SqliteOptions>,
+}
+
+/// Settings applied to every connection of a SQLite pool.
+#[cfg(feature = "sqlite")]
+#[derive(Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SqliteOptions {
+    /// The directory the path of the database is relative to.
+    base_dir: Option<BaseDir>,
+    /// `delete`, `truncate`, `persist`, `memory`, `wal` or `off`.
+    journal_mode: Option<String>,
+    /// Seconds to wait for a locked database before failing.
+    busy_timeout: Option<f64>,
+    foreign_keys: Option<bool>,
+    /// `off`, `normal`, `full` or `extra`.
+    synchronous: Option<String>,
+    /// Other pragmas, run in the order of their names. Only accepted from
+    /// the plugin config.
+    #[serde(default)]
+    pragmas: BTreeMap<String, JsonValue>,
+    /// The SQLCipher key of the database.
+    #[cfg(feature = "sqlcipher")]
+    key: Option<String>,
+}
+
+#[cfg(feature = "sqlite")]
+impl SqliteOptions {
+    fn apply(&self, mut options: SqliteConnectOptions) -> Result<SqliteConnectOptions> {
+        // sqlx runs the `key` pragma before any other
+        #[cfg(feature = "sqlcipher")]
+        if let Some(key) = &self.key {
+            options = options.pragma("key", quote_key(key));
+        }
+        if let Some(journal_mode) = &self.journal_mode {
+            options = options.journal_mode(SqliteJournalMode::from_str(journal_mode)?);
+        }
+        if let Some(busy_timeout) = self.busy_timeout {
+            let busy_timeout = Duration::try_from_secs_f64(busy_timeout).map_err(|_| {
+                Error::InvalidValue("busy_timeout".to_string(), busy_timeout.to_string())
+            })?;
+            options = options.busy_timeout(busy_timeout);
+        }
+        if let Some(foreign_keys) = self.foreign_keys {
+            options = options.foreign_keys(foreign_keys);
+        }
+        if let Some(synchronous) = &self.synchronous {
+            options = options.synchronous(SqliteSynchronous::from_str(synchronous)?);
+        }
+        for (key, value) in &self.pragmas {
+            options = options.pragma(key.clone(), pragma_value(key, value)?);
+        }
+        Ok(options)
+    }
+
+    /// Fills the options that are not set with those of `defaults`.
+    fn or(self, defaults: &Self) -> Self {
+        let mut pragmas = defaults.pragmas.clone();
+        pragmas.extend(self.pragmas);
+        Self {
+            base_dir: self.base_dir.or(defaults.base_dir),
+            journal_mode: self.journal_mode.or_else(|| defaults.journal_mode.clone()),
+            busy_timeout: self.busy_timeout.or(defaults.busy_timeout),
+            foreign_keys: self.foreign_keys.or(defaults.foreign_keys),
+            synchronous: self.synchronous.or_else(|| defaults.synchronous.clone()),
+            pragmas,
+            #[cfg(feature = "sqlcipher")]
+            key: self.key.or_else(|| defaults.key.clone()),
+        }
+    }
+}
+
+/// Checks a pragma and formats its value, as sqlx runs
+/// `PRAGMA {key} = {value}` without quoting either. Names must be plain
+/// identifiers and values numbers or plain words, and the SQLCipher keys
+/// can only be set through the `key` option and `rekey`.
+#[cfg(feature = "sqlite")]
+fn pragma_value(key: &str, value: &JsonValue) -> Result<String> {
+    let is_word =
+        |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_');
+    if !is_word(key) || key.as_bytes()[0].is_ascii_digit() {
+        return Err(Error::InvalidValue("pragma".to_string(), key.to_string()));
+    }
+    if key.eq_ignore_ascii_case("key") || key.eq_ignore_ascii_case("rekey") {
+        return Err(Error::Unsupported(format!(
+            "the {key} pragma cannot be set in `pragmas`"
+        )));
+    }
+    match value {
+        JsonValue::Number(value) => Ok(value.to_string()),
+        JsonValue::Bool(value) => Ok(value.to_string()),
+        JsonValue::String(value) if is_word(value) || value.parse::<f64>().is_ok() => {
+            Ok(value.clone())
+        }
+        _ => Err(Error::InvalidValue(
+            format!("pragma {key}"),
+            value.to_string(),
+        )),
+    }
+}
+
+impl ConnectionOptions {
+    /// Fills the options that are not set with those of `defaults`.
+    pub(crate) fn or(self, defaults: Option<&Self>) -> Self {
+        let Some(defaults) = defaults else {
+            return self;
+        };
+        Self {
+            max_connections: self.max_connections.or(defaults.max_connections),
+            min_connections: self.min_connections.or(defaults.min_connections),
+            acquire_timeout: self.acquire_timeout.or(defaults.acquire_timeout),
+            idle_timeout: self.idle_timeout.or(defaults.idle_timeout),
+            test_before_acquire: self.test_before_acquire.or(defaults.test_before_acquire),
+            // the frontend cannot lift a read-only mode set in the config
+            read_only: match defaults.read_only {
+                Some(true) => Some(true),
+                _ => self.read_only.or(defaults.read_only),
+            },
+            #[cfg(feature = "sqlite")]
+            sqlite: match (self.sqlite, &defaults.sqlite) {
+                (Some(sqlite), Some(defaults)) => Some(sqlite.or(defaults)),
+                (sqlite, defaults) => sqlite.or_else(|| defaults.clone()),
+            },
+        }
+    }
+
+    /// Whether the options set pragmas, which the frontend may not.
+    #[cfg(feature = "sqlite")]
+    pub(crate) fn has_pragmas(&self) -> bool {
+        self.sqlite
+            .as_ref()
+            .is_some_and(|sqlite| !sqlite.pragmas.is_empty())
+    }
+
+    pub(crate) fn is_read_only(&self) -> bool {
+        self.read_only.unwrap_or(false)
+    }
+
+    /// Opens the database read-only, whatever the options set.
+    #[cfg(feature = "sqlite")]
+    pub(crate) fn with_read_only(mut self) -> Self {
+        self.read_only = Some(true);
+        self
+    }
+
+    #[cfg(feature = "sqlite")]
+    fn base_dir(&self) -> BaseDir {
+        self.sqlite
+            .as_ref()
+            .and_then(|sqlite| sqlite.base_dir)
+            .unwrap_or_default()
+    }
+
+    /// Sets the SQLCipher key, overriding the one of the options.
+    #[cfg(feature = "sqlcipher")]
+    pub(crate) fn with_key(mut self, key: Option<String>) -> Self {
+        if let Some(key) = key {
+            self.sqlite.get_or_insert_with(Default::default).key = Some(key);
+        }
+        self
+    }
+
+    fn pool_options<DB: Database>(&self) -> PoolOptions<DB> {
+        let mut options = PoolOptions::new();
+        if let Some(max_connections) = self.max_connections {
+            options = options.max_connections(max_connections);
+        }
+        if let Some(min_connections) = self.min_connections {
+            options = options.min_connections(min_connections);
+        }
+        if let Some(acquire_timeout) = self.acquire_timeout {
+            options = options.acquire_timeout(Duration::from_secs(acquire_timeout));
+        }
+        if let Some(idle_timeout) = self.idle_timeout {
+            options = options.idle_timeout(Duration::from_secs(idle_timeout));
+        }
+        if let Some(test_before_acquire) = self.test_before_acquire {
+            options = options.test_before_acquire(test_before_acquire);
+        }
+        options
+    }
+}
+
+/// Changes the key of the database with `PRAGMA rekey`, run on a connection
+/// of its own, and returns a pool with the options of `pool` and the new key,
+/// as the connections of `pool` keep using the old one. This works with
+/// every journal mode, WAL included.
+///
+/// Once the key is changed, the old pool cannot open connections anymore,
+/// so the new one connects lazily: it is returned even if connecting with
+/// the new key fails, which then surfaces on its first query.
+#[cfg(feature = "sqlcipher")]
+async fn rekey_sqlite(pool: &Pool<Sqlite>, key: &str) -> Result<Pool<Sqlite>> {
+    let mut conn = pool.connect_options().connect().await?;
+    sqlx::query(&format!("PRAGMA rekey = {}", quote_key(key)))
+        .execute(&mut conn)
+        .await?;
+    if let Err(e) = conn.close().await {
+        log::warn!("failed to close the connection used to rekey the database: {e}");
+    }
+
+    let options = (*pool.connect_options())
+        .clone()
+        .pragma("key", quote_key(key));
+    Ok(pool.options().clone().connect_lazy_with(options))
+}
+
+/// Quotes a SQLCipher key for the `key` and `rekey` pragmas, which do not
+/// take bind parameters.
+#[cfg(feature = "sqlcipher")]
+fn quote_key(key: &str) -> String {
+    format!("'{}'", key.replace('\'', "''"))
+}
+
+/// A connection pool for any of the enabled database drivers.
+///
+/// The driver is picked from the scheme of the connection string the
+/// database was loaded with. Cloning it is cheap and returns a handle to
+/// the same pool, see [`SqlExt::db_pool`](crate::SqlExt::db_pool).
+#[derive(Debug, Clone)]
+pub enum DbPool {
+    #[cfg(feature = "sqlite")]
+    Sqlite(Pool<Sqlite>),
+    #[cfg(feature = "mysql")]
+    MySql(Pool<MySql>),
+    #[cfg(feature = "postgres")]
+    Postgres(Pool<Postgres>),
+}
+
+impl DbPool {
+    pub(crate) fn placeholders(&self) -> Placeholders {
+        match self {
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(_) => Placeholders::Numbered,
+            #[cfg(feature = "mysql")]
+            Self::MySql(_) => Placeholders::Question,
+            #[cfg(feature = "postgres")]
+            Self::Postgres(_) => Placeholders::Numbered,
+        }
+    }
+
+    /// Creates the database if it does not exist yet and connects to it
+    /// with the driver matching the scheme of `conn_url`.
+    pub(crate) async fn connect<R: Runtime>(
+        conn_url: &str,
+        #[allow(unused_variables)] app: &AppHandle<R>,
+        options: &ConnectionOptions,
+        #[allow(unused_variables)] allow_external_paths: bool,
+    ) -> Result<Self> {
+        match conn_url.split_once(':').map(|(scheme, _)| scheme) {
+            #[cfg(feature = "sqlite")]
+            Some("sqlite") | Some("file") => {
+                let in_memory = is_in_memory(conn_url);
+                let connect_options = if in_memory {
+                    SqliteConnectOptions::from_str(conn_url)?
+                } else if conn_url.starts_with("file:") {
+                    return Err(Error::InvalidDbUrl(conn_url.to_string()));
+                } else {
+                    let app_path = app_path(app, options.base_dir())?;
+                    create_dir_all(&app_path)?;
+
+                    let fqdb = path_mapper(app_path, conn_url, allow_external_paths)?;
+                    if !options.is_read_only()
+                        && !Sqlite::database_exists(&fqdb).await.unwrap_or(false)
+                    {
+                        Sqlite::create_database(&fqdb).await?;
+                    }
+                    SqliteConnectOptions::from_str(&fqdb)?
+                };
+                let connect_options = connect_options.read_only(options.is_read_only());
+                let connect_options = match &options.sqlite {
+                    Some(sqlite) => sqlite.apply(connect_options)?,
+                    None => connect_options,
+                };
+
+                let mut pool_options = options.pool_options();
+                if in_memory {
+                    // an in-memory database is gone once its last connection
+                    // closes, so one connection is kept open until the pool is
+                    let min_connections = pool_options.get_min_connections().max(1);
+                    pool_options = pool_options
+                        .min_connections(min_connections)
+                        .idle_timeout(None)
+                        .max_lifetime(None);
+                }
+                Ok(Self::Sqlite(
+                    pool_options.connect_with(connect_options).await?,
+                ))
+            }
+            #[cfg(feature = "mysql")]
+            Some("mysql") => {
+                if !options.is_read_only()
+                    && !MySql::database_exists(conn_url).await.unwrap_or(false)
+                {
+                    MySql::create_database(conn_url).await?;
+                }
+                let mut pool_options = options.pool_options::<MySql>();
+                if options.is_read_only() {
+                    pool_options = pool_options.after_connect(|conn, _| {
+                        Box::pin(async move {
+                            conn.execute("SET SESSION TRANSACTION READ ONLY").await?;
+                            Ok(())
+                        })
+                    });
+                }
+                Ok(Self::MySql(pool_options.connect(conn_url).await?))
+            }
+            #[cfg(feature = "postgres")]
+            Some("postgres") | Some("postgresql") => {
+                if !options.is_read_only()
+                    && !Postgres::database_exists(conn_url).await.unwrap_or(false)
+                {
+                    Postgres::create_database(conn_url).await?;
+                }
+                let mut connect_options = PgConnectOptions::from_str(conn_url)?;
+                if options.is_read_only() {
+                    connect_options =
+                        connect_options.options([("default_transaction_read_only", "on")]);
+                }
+                Ok(Self::Postgres(
+                    options.pool_options().connect_with(connect_options).await?,
+                ))
+            }
+            _ => Err(Error::InvalidDbUrl(conn_url.to_string())),
+        }
+    }
+
+    /// The file of the SQLite database `conn_url` refers to, resolved like
+    /// [`DbPool::connect`] does, or `None` for in-memory databases and the
+    /// other drivers.
+    #[cfg(feature = "sqlite")]
+    pub(crate) fn sqlite_file<R: Runtime>(
+        conn_url: &str,
+        app: &AppHandle<R>,
+        options: &ConnectionOptions,
+        allow_external_paths: bool,
+    ) -> Result<Option<PathBuf>> {
+        if !conn_url.starts_with("sqlite:") || is_in_memory(conn_url) {
+            return Ok(None);
+        }
+        let app_path = app_path(app, options.base_dir())?;
+        database_file(app_path, conn_url, allow_external_paths).map(Some)
+    }
+
+    pub(crate) async fn migrate(&self, migrator: &Migrator) -> Result<()> {
+        match self {
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(pool) => migrator.run(pool).await?,
+            #[cfg(feature = "mysql")]
+            Self::MySql(pool) => migrator.run(pool).await?,
+            #[cfg(feature = "postgres")]
+            Self::Postgres(pool) => migrator.run(pool).await?,
+        }
+        Ok(())
+    }
+
+    /// Reverts every applied migration with a version above `target`.
+    pub(crate) async fn undo(&self, migrator: &Migrator, target: i64) -> Result<()> {
+        match self {
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(pool) => migrator.undo(pool, target).await?,
+            #[cfg(feature = "mysql")]
+            Self::MySql(pool) => migrator.undo(pool, target).await?,
+            #[cfg(feature = "postgres")]
+            Self::Postgres(pool) => migrator.undo(pool, target).await?,
+        }
+        Ok(())
+    }
+
+    /// Lists the migrations recorded as applied in the database, ordered
+    /// by version.
+    pub(crate) async fn applied_migrations(&self) -> Result<Vec<AppliedMigration>> {
+        let applied = match self {
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(pool) => {
+                let mut conn = pool.acquire().await?;
+                conn.ensure_migrations_table().await?;
+                conn.list_applied_migrations().await?
+            }
+            #[cfg(feature = "mysql")]
+            Self::MySql(pool) => {
+                let mut conn = pool.acquire().await?;
+                conn.ensure_migrations_table().await?;
+                conn.list_applied_migrations().await?
+            }
+            #[cfg(feature = "postgres")]
+            Self::Postgres(pool) => {
+                let mut conn = pool.acquire().await?;
+                conn.ensure_migrations_table().await?;
+                conn.list_applied_migrations().await?
+            }
+        };
+        Ok(applied)
+    }
+
+    /// Reads the rows of the `_sqlx_migrations` table, ordered by version.
+    ///
+    /// The table is created if it is missing, unless the database is
+    /// `read_only`, in which case a missing table has no rows.
+    pub(crate) async fn migration_records(&self, read_only: bool) -> Result<Vec<MigrationRecord>> {
+        // `installed_on` is cast to text as its type differs between drivers
+        let records: Vec<(i64, Vec<u8>, String, bool)> = match self {
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(pool) => {
+                let mut conn = pool.acquire().await?;
+                if !read_only {
+                    conn.ensure_migrations_table().await?;
+                } else if !sqlx::query_scalar(
+                    "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = '_sqlx_migrations')",
+                )
+                .fetch_one(&mut *conn)
+                .await?
+                {
+                    return Ok(Vec::new());
+                }
+                sqlx::query_as(
+                    "SELECT version, checksum, CAST(installed_on AS TEXT), success FROM _sqlx_migrations ORDER BY version",
+                )
+                .fetch_all(&mut *conn)
+                .await?
+            }
+            #[cfg(feature = "mysql")]
+            Self::MySql(pool) => {
+                let mut conn = pool.acquire().await?;
+                if !read_only {
+                    conn.ensure_migrations_table().await?;
+                } else if !sqlx::query_scalar(
+                    "SELECT EXISTS (SELECT 1 FROM information_schema.tables WHERE table_schema = DATABASE() AND table_name = '_sqlx_migrations')",
+                )
+                .fetch_one(&mut *conn)
+                .await?
+                {
+                    return Ok(Vec::new());
+                }
+                sqlx::query_as(
+                    "SELECT version, checksum, CAST(installed_on AS CHAR), success FROM _sqlx_migrations ORDER BY version",
+                )
+                .fetch_all(&mut *conn)
+                .await?
+            }
+            #[cfg(feature = "postgres")]
+            Self::Postgres(pool) => {
+                let mut conn = pool.acquire().await?;
+                if !read_only {
+                    conn.ensure_migrations_table().await?;
+                } else if !sqlx::query_scalar("SELECT to_regclass('_sqlx_migrations') IS NOT NULL")
+                    .fetch_one(&mut *conn)
+                    .await?
+                {
+                    return Ok(Vec::new());
+                }
+                sqlx::query_as(
+                    "SELECT version, checksum, CAST(installed_on AS TEXT), success FROM _sqlx_migrations ORDER BY version",
+                )
+                .fetch_all(&mut *conn)
+                .await?
+            }
+        };
+        Ok(records
+            .into_iter()
+            .map(
+                |(version, checksum, installed_on, success)| MigrationRecord {
+                    version,
+                    checksum,
+                    installed_on,
+                    success,
+                },
+            )
+            .collect())
+    }
+
+    /// Changes the SQLCipher key of the database and returns a new pool
+    /// using it, as the connections of this pool keep using the old key.
+    /// The caller replaces this pool with the new one, then closes it.
+    pub(crate) async fn rekey(&self, #[allow(unused_variables)] key: &str) -> Result<Self> {
+        match self {
+            #[cfg(feature = "sqlcipher")]
+            Self::Sqlite(pool) => Ok(Self::Sqlite(rekey_sqlite(pool, key).await?)),
+            #[allow(unreachable_patterns)]
+            _ => Err(Error::Unsupported(
+                "rekey requires a SQLite database and the `sqlcipher` feature".to_string(),
+            )),
+        }
+    }
+
+    pub(crate) async fn close(&self) {
+        match self {
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(pool) => pool.close().await,
+            #[cfg(feature = "mysql")]
+            Self::MySql(pool) => pool.close().await,
+            #[cfg(feature = "postgres")]
+            Self::Postgres(pool) => pool.close().await,
+        }
+    }
+
+    /// Opens the value of `column` in the row `row_id` of `table` for
+    /// incremental I/O, on a connection checked out of the pool until the
+    /// blob is closed.
+    pub(crate) async fn open_blob(
+        &self,
+        #[allow(unused_variables)] table: &str,
+        #[allow(unused_variables)] column: &str,
+        #[allow(unused_variables)] row_id: i64,
+        #[allow(unused_variables)] writable: bool,
+    ) -> Result<DbBlob> {
+        match self {
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(pool) => {
+                let conn = pool.acquire().await?;
+                let blob = SqliteBlob::open(conn, table, column, row_id, writable).await?;
+                Ok(DbBlob::Sqlite(blob))
+            }
+            #[allow(unreachable_patterns)]
+            _ => Err(Error::Unsupported(
+                "incremental blob I/O requires a SQLite database".to_string(),
+            )),
+        }
+    }
+
+    /// Checks out a dedicated connection from the pool and starts a
+    /// transaction on it.
+    pub(crate) async fn begin(&self) -> Result<DbTransaction> {
+        match self {
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(pool) => Ok(DbTransaction::Sqlite(pool.begin().await?)),
+            #[cfg(feature = "mysql")]
+            Self::MySql(pool) => Ok(DbTransaction::MySql(pool.begin().await?)),
+            #[cfg(feature = "postgres")]
+            Self::Postgres(pool) => Ok(DbTransaction::Postgres(pool.begin().await?)),
+        }
+    }
+
+    /// Like [`DbPool::begin`], with a transaction that cannot write to the
+    /// database. SQLite connections of read-only databases are already
+    /// opened read-only, so they start a regular transaction.
+    pub(crate) async fn begin_read_only(&self) -> Result<DbTransaction> {
+        match self {
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(pool) => Ok(DbTransaction::Sqlite(pool.begin().await?)),
+            #[cfg(feature = "mysql")]
+            Self::MySql(pool) => Ok(DbTransaction::MySql(
+                pool.begin_with("START TRANSACTION READ ONLY").await?,
+            )),
+            #[cfg(feature = "postgres")]
+            Self::Postgres(pool) => Ok(DbTransaction::Postgres(
+                pool.begin_with("BEGIN READ ONLY").await?,
+            )),
+        }
+    }
+
+    /// Executes a statement with the same binding of `values` as the
+    /// `execute` command and returns the number of rows affected along with
+    /// the last inserted id.
+    pub async fn execute(
+        &self,
+        query: &str,
+        values: Vec<JsonValue>,
+    ) -> Result<(u64, LastInsertId)> {
+        match self {
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(pool) => {
+                let result = bind_values(sqlx::query(query), values)?
+                    .execute(pool)
+                    .await?;
+                Ok(sqlite_result(result))
+            }
+            #[cfg(feature = "mysql")]
+            Self::MySql(pool) => {
+                let result = bind_values(sqlx::query(query), values)?
+                    .execute(pool)
+                    .await?;
+                Ok(mysql_result(result))
+            }
+            #[cfg(feature = "postgres")]
+            Self::Postgres(pool) => {
+                let result =
+                    bind_values(sqlx::query(&Postgres::statement(query, &values)), values)?
+                        .execute(pool)
+                        .await?;
+                Ok(postgres_result(result))
+            }
+        }
+    }
+
+    /// Runs every statement of the batch inside a single transaction,
+    /// which is only committed if all of them succeed.
+    pub(crate) async fn execute_batch(
+        &self,
+        statements: Vec<BatchStatement>,
+    ) -> Result<Vec<(u64, LastInsertId)>> {
+        let mut transaction = self.begin().await?;
+        let results = transaction.execute_batch(statements).await?;
+        transaction.commit().await?;
+        Ok(results)
+    }
+
+    /// Runs a query with the same binding and decoding as the `select`
+    /// command and returns its rows.
+    pub async fn select(
+        &self,
+        query: &str,
+        values: Vec<JsonValue>,
+        decode: DecodeOptions,
+    ) -> Result<Vec<HashMap<String, JsonValue>>> {
+        match self {
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(pool) => {
+                let rows = bind_values(sqlx::query(query), values)?
+                    .fetch_all(pool)
+                    .await?;
+                rows_to_json::<Sqlite>(rows, crate::decode::sqlite::to_json, decode)
+            }
+            #[cfg(feature = "mysql")]
+            Self::MySql(pool) => {
+                let rows = bind_values(sqlx::query(query), values)?
+                    .fetch_all(pool)
+                    .await?;
+                rows_to_json::<MySql>(rows, crate::decode::mysql::to_json, decode)
+            }
+            #[cfg(feature = "postgres")]
+            Self::Postgres(pool) => {
+                let rows = bind_values(sqlx::query(&Postgres::statement(query, &values)), values)?
+                    .fetch_all(pool)
+                    .await?;
+                rows_to_json::<Postgres>(rows, crate::decode::postgres::to_json, decode)
+            }
+        }
+    }
+
+    /// Runs a query and returns the first column of its first row as bytes,
+    /// or `None` if there is no row or the value is `NULL`.
+    pub(crate) async fn read_blob(
+        &self,
+        query: &str,
+        values: Vec<JsonValue>,
+    ) -> Result<Option<Vec<u8>>> {
+        match self {
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(pool) => {
+                let row = bind_values(sqlx::query(query), values)?
+                    .fetch_optional(pool)
+                    .await?;
+                first_blob::<Sqlite>(row)
+            }
+            #[cfg(feature = "mysql")]
+            Self::MySql(pool) => {
+                let row = bind_values(sqlx::query(query), values)?
+                    .fetch_optional(pool)
+                    .await?;
+                first_blob::<MySql>(row)
+            }
+            #[cfg(feature = "postgres")]
+            Self::Postgres(pool) => {
+                let row = bind_values(sqlx::query(&Postgres::statement(query, &values)), values)?
+                    .fetch_optional(pool)
+                    .await?;
+                first_blob::<Postgres>(row)
+            }
+        }
+    }
+
+    pub(crate) async fn select_columns(
+        &self,
+        query: &str,
+        values: Vec<JsonValue>,
+        decode: DecodeOptions,
+    ) -> Result<ColumnarRows> {
+        match self {
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(pool) => {
+                let mut conn = pool.acquire().await?;
+                select_columns::<Sqlite>(
+                    &mut conn,
+                    query,
+                    values,
+                    crate::decode::sqlite::to_json,
+                    decode,
+                )
+                .await
+            }
+            #[cfg(feature = "mysql")]
+            Self::MySql(pool) => {
+                let mut conn = pool.acquire().await?;
+                select_columns::<MySql>(
+                    &mut conn,
+                    query,
+                    values,
+                    crate::decode::mysql::to_json,
+                    decode,
+                )
+                .await
+            }
+            #[cfg(feature = "postgres")]
+            Self::Postgres(pool) => {
+                let mut conn = pool.acquire().await?;
+                select_columns::<Postgres>(
+                    &mut conn,
+                    query,
+                    values,
+                    crate::decode::postgres::to_json,
+                    decode,
+                )
+                .await
+            }
+        }
+    }
+
+    /// Runs a query in the background, fetching its rows incrementally
+    /// instead of all at once. The rows are handed out in chunks of
+    /// `chunk_size` through the returned [`Cursor`].
+    pub(crate) fn stream(
+        &self,
+        query: String,
+        values: Vec<JsonValue>,
+        decode: DecodeOptions,
+        chunk_size: usize,
+    ) -> Cursor {
+        // a single chunk is buffered, so the query only advances as fast as
+        // the frontend consumes it
+        let (sender, receiver) = mpsc::channel(1);
+        let task = match self {
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(pool) => {
+                let pool = pool.clone();
+                spawn(async move {
+                    let rows =
+                        bind_values(sqlx::query(&query), values).map(|query| query.fetch(&pool));
+                    stream_rows::<Sqlite>(
+                        rows,
+                        crate::decode::sqlite::to_json,
+                        decode,
+                        chunk_size,
+                        sender,
+                    )
+                    .await
+                })
+            }
+            #[cfg(feature = "mysql")]
+            Self::MySql(pool) => {
+                let pool = pool.clone();
+                spawn(async move {
+                    let rows =
+                        bind_values(sqlx::query(&query), values).map(|query| query.fetch(&pool));
+                    stream_rows::<MySql>(
+                        rows,
+                        crate::decode::mysql::to_json,
+                        decode,
+                        chunk_size,
+                        sender,
+                    )
+                    .await
+                })
+            }
+            #[cfg(feature = "postgres")]
+            Self::Postgres(pool) => {
+                let pool = pool.clone();
+                spawn(async move {
+                    let statement = Postgres::statement(&query, &values);
+                    let rows = bind_values(sqlx::query(&statement), values)
+                        .map(|query| query.fetch(&pool));
+                    stream_rows::<Postgres>(
+                        rows,
+                        crate::decode::postgres::to_json,
+                        decode,
+                        chunk_size,
+                        sender,
+                    )
+                    .await
+                })
+            }
+        };
+        Cursor { receiver, task }
+    }
+}
+
+/// The receiving end of a query started with [`DbPool::stream`].
+///
+/// Dropping it stops the query and returns its connection to the pool.
+pub(crate) struct Cursor {
+    receiver: mpsc::Receiver<Result<Vec<HashMap<String, JsonValue>>>>,
+    task: JoinHandle<()>,
+}
+
+impl Cursor {
+    /// Waits for the next chunk of rows, or `None` once all rows have
+    /// been returned.
+    pub(crate) async fn next(&mut self) -> Result<Option<Vec<HashMap<String, JsonValue>>>> {
+        self.receiver.recv().await.transpose()
+    }
+}
+
+impl Drop for Cursor {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// A statement of an `execute_batch` call, run once for every set of
+/// bind values, or once without any when `values` is not given. An empty
+/// list of sets runs nothing.
+#[derive(Debug, Deserialize)]
+pub(crate) struct BatchStatement {
+    query: String,
+    values: Option<Vec<BindValues>>,
+}
+
+/// An open transaction on a connection checked out of a [`DbPool`].
+///
+/// Dropping it without calling [`DbTransaction::commit`] rolls the
+/// transaction back.
+pub(crate) enum DbTransaction {
+    #[cfg(feature = "sqlite")]
+    Sqlite(Transaction<'static, Sqlite>),
+    #[cfg(feature = "mysql")]
+    MySql(Transaction<'static, MySql>),
+    #[cfg(feature = "postgres")]
+    Postgres(Transaction<'static, Postgres>),
+}
+
+impl DbTransaction {
+    pub(crate) fn placeholders(&self) -> Placeholders {
+        match self {
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(_) => Placeholders::Numbered,
+            #[cfg(feature = "mysql")]
+            Self::MySql(_) => Placeholders::Question,
+            #[cfg(feature = "postgres")]
+            Self::Postgres(_) => Placeholders::Numbered,
+        }
+    }
+
+    pub(crate) async fn commit(self) -> Result<()> {
+        match self {
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(tx) => tx.commit().await?,
+            #[cfg(feature = "mysql")]
+            Self::MySql(tx) => tx.commit().await?,
+            #[cfg(feature = "postgres")]
+            Self::Postgres(tx) => tx.commit().await?,
+        }
+        Ok(())
+    }
+
+    pub(crate) async fn rollback(self) -> Result<()> {
+        match self {
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(tx) => tx.rollback().await?,
+            #[cfg(feature = "mysql")]
+            Self::MySql(tx) => tx.rollback().await?,
+            #[cfg(feature = "postgres")]
+            Self::Postgres(tx) => tx.rollback().await?,
+        }
+        Ok(())
+    }
+
+    pub(crate) async fn execute(
+        &mut self,
+        query: &str,
+        values: Vec<JsonValue>,
+    ) -> Result<(u64, LastInsertId)> {
+        match self {
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(tx) => {
+                let result = bind_values(sqlx::query(query), values)?
+                    .execute(&mut **tx)
+                    .await?;
+                Ok(sqlite_result(result))
+            }
+            #[cfg(feature = "mysql")]
+            Self::MySql(tx) => {
+                let result = bind_values(sqlx::query(query), values)?
+                    .execute(&mut **tx)
+                    .await?;
+                Ok(mysql_result(result))
+            }
+            #[cfg(feature = "postgres")]
+            Self::Postgres(tx) => {
+                let result =
+                    bind_values(sqlx::query(&Postgres::statement(query, &values)), values)?
+                        .execute(&mut **tx)
+                        .await?;
+                Ok(postgres_result(result))
+            }
+        }
+    }
+
+    pub(crate) async fn execute_batch(
+        &mut self,
+        statements: Vec<BatchStatement>,
+    ) -> Result<Vec<(u64, LastInsertId)>> {
+        let mut results = Vec::new();
+        for statement in statements {
+            let Some(sets) = statement.values else {
+                results.push(self.execute(&statement.query, Vec::new()).await?);
+                continue;
+            };
+            for values in sets {
+                let (query, values) =
+                    values.into_positional(&statement.query, self.placeholders())?;
+                results.push(self.execute(&query, values).await?);
+            }
+        }
+        Ok(results)
+    }
+
+    pub(crate) async fn select(
+        &mut self,
+        query: &str,
+        values: Vec<JsonValue>,
+        decode: DecodeOptions,
+    ) -> Result<Vec<HashMap<String, JsonValue>>> {
+        match self {
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(tx) => {
+                let rows = bind_values(sqlx::query(query), values)?
+                    .fetch_all(&mut **tx)
+                    .await?;
+                rows_to_json::<Sqlite>(rows, crate::decode::sqlite::to_json, decode)
+            }
+            #[cfg(feature = "mysql")]
+            Self::MySql(tx) => {
+                let rows = bind_values(sqlx::query(query), values)?
+                    .fetch_all(&mut **tx)
+                    .await?;
+                rows_to_json::<MySql>(rows, crate::decode::mysql::to_json, decode)
+            }
+            #[cfg(feature = "postgres")]
+            Self::Postgres(tx) => {
+                let rows = bind_values(sqlx::query(&Postgres::statement(query, &values)), values)?
+                    .fetch_all(&mut **tx)
+                    .await?;
+                rows_to_json::<Postgres>(rows, crate::decode::postgres::to_json, decode)
+            }
+        }
+    }
+
+    /// Runs a query and returns the first column of its first row as bytes,
+    /// or `None` if there is no row or the value is `NULL`.
+    pub(crate) async fn read_blob(
+        &mut self,
+        query: &str,
+        values: Vec<JsonValue>,
+    ) -> Result<Option<Vec<u8>>> {
+        match self {
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(tx) => {
+                let row = bind_values(sqlx::query(query), values)?
+                    .fetch_optional(&mut **tx)
+                    .await?;
+                first_blob::<Sqlite>(row)
+            }
+            #[cfg(feature = "mysql")]
+            Self::MySql(tx) => {
+                let row = bind_values(sqlx::query(query), values)?
+                    .fetch_optional(&mut **tx)
+                    .await?;
+                first_blob::<MySql>(row)
+            }
+            #[cfg(feature = "postgres")]
+            Self::Postgres(tx) => {
+                let row = bind_values(sqlx::query(&Postgres::statement(query, &values)), values)?
+                    .fetch_optional(&mut **tx)
+                    .await?;
+                first_blob::<Postgres>(row)
+            }
+        }
+    }
+
+    pub(crate) async fn select_columns(
+        &mut self,
+        query: &str,
+        values: Vec<JsonValue>,
+        decode: DecodeOptions,
+    ) -> Result<ColumnarRows> {
+        match self {
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(tx) => {
+                select_columns::<Sqlite>(tx, query, values, crate::decode::sqlite::to_json, decode)
+                    .await
+            }
+            #[cfg(feature = "mysql")]
+            Self::MySql(tx) => {
+                select_columns::<MySql>(tx, query, values, crate::decode::mysql::to_json, decode)
+                    .await
+            }
+            #[cfg(feature = "postgres")]
+            Self::Postgres(tx) => {
+                select_columns::<Postgres>(
+                    tx,
+                    query,
+                    values,
+                    crate::decode::postgres::to_json,
+                    decode,
+                )
+                .await
+            }
+        }
+    }
+
+    /// Like [`DbPool::stream`], running the query in this transaction,
+    /// which is rolled back once the [`Cursor`] is dropped.
+    pub(crate) fn stream(
+        self,
+        query: String,
+        values: Vec<JsonValue>,
+        decode: DecodeOptions,
+        chunk_size: usize,
+    ) -> Cursor {
+        let (sender, receiver) = mpsc::channel(1);
+        let task = match self {
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(mut tx) => spawn(async move {
+                let rows =
+                    bind_values(sqlx::query(&query), values).map(|query| query.fetch(&mut *tx));
+                stream_rows::<Sqlite>(
+                    rows,
+                    crate::decode::sqlite::to_json,
+                    decode,
+                    chunk_size,
+                    sender,
+                )
+                .await
+            }),
+            #[cfg(feature = "mysql")]
+            Self::MySql(mut tx) => spawn(async move {
+                let rows =
+                    bind_values(sqlx::query(&query), values).map(|query| query.fetch(&mut *tx));
+                stream_rows::<MySql>(
+                    rows,
+                    crate::decode::mysql::to_json,
+                    decode,
+                    chunk_size,
+                    sender,
+                )
+                .await
+            }),
+            #[cfg(feature = "postgres")]
+            Self::Postgres(mut tx) => spawn(async move {
+                let statement = Postgres::statement(&query, &values);
+                let rows =
+                    bind_values(sqlx::query(&statement), values).map(|query| query.fetch(&mut *tx));
+                stream_rows::<Postgres>(
+                    rows,
+                    crate::decode::postgres::to_json,
+                    decode,
+                    chunk_size,
+                    sender,
+                )
+                .await
+            }),
+        };
+        Cursor { receiver, task }
+    }
+}
+
+#[cfg(feature = "sqlite")]
+fn sqlite_result(result: SqliteQueryResult) -> (u64, LastInsertId) {
+    (
+        result.rows_affected(),
+        LastInsertId::Sqlite(result.last_insert_rowid()),
+    )
+}
+
+#[cfg(feature = "mysql")]
+fn mysql_result(result: MySqlQueryResult) -> (u64, LastInsertId) {
+    (
+        result.rows_affected(),
+        LastInsertId::MySql(result.last_insert_id()),
+    )
+}
+
+#[cfg(feature = "postgres")]
+fn postgres_result(result: PgQueryResult) -> (u64, LastInsertId) {
+    (result.rows_affected(), LastInsertId::Postgres(0))
+}
+
+/// Converts fetched rows into JSON objects keyed by column name, using
+/// the driver specific `to_json` decoder.
+fn rows_to_json<DB: Database>(
+    rows: Vec<DB::Row>,
+    to_json: for<'r> fn(DB::ValueRef<'r>, DecodeOptions) -> Result<JsonValue>,
+    decode: DecodeOptions,
+) -> Result<Vec<HashMap<String, JsonValue>>>
+where
+    usize: ColumnIndex<DB::Row>,
+{
+    rows.iter()
+        .map(|row| row_to_json::<DB>(row, to_json, decode))
+        .collect()
+}
+
+fn row_to_json<DB: Database>(
+    row: &DB::Row,
+    to_json: for<'r> fn(DB::ValueRef<'r>, DecodeOptions) -> Result<JsonValue>,
+    decode: DecodeOptions,
+) -> Result<HashMap<String, JsonValue>>
+where
+    usize: ColumnIndex<DB::Row>,
+{
+    let mut value = HashMap::default();
+    for (i, column) in row.columns().iter().enumerate() {
+        let v = row.try_get_raw(i)?;
+        let v = to_json(v, decode)?;
+        value.insert(column.name().to_string(), v);
+    }
+    Ok(value)
+}
+
+fn first_blob<DB: Database>(row: Option<DB::Row>) -> Result<Option<Vec<u8>>>
+where
+    for<'r> Vec<u8>: sqlx::Decode<'r, DB> + Type<DB>,
+    usize: ColumnIndex<DB::Row>,
+{
+    match row {
+        Some(row) => Ok(row.try_get::<Option<Vec<u8>>, _>(0)?),
+        None => Ok(None),
+    }
+}
+
+/// Runs a query on `conn` and returns its rows as arrays of values, along
+/// with the metadata of its columns. Unlike [`rows_to_json`], this keeps
+/// the column order and duplicated column names.
+async fn select_columns<DB: BindExact>(
+    conn: &mut DB::Connection,
+    query: &str,
+    values: Vec<JsonValue>,
+    to_json: for<'r> fn(DB::ValueRef<'r>, DecodeOptions) -> Result<JsonValue>,
+    decode: DecodeOptions,
+) -> Result<ColumnarRows>
+where
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'q> JsonValue: Encode<'q, DB> + Type<DB>,
+    for<'q> String: Encode<'q, DB> + Type<DB>,
+    for<'q> i64: Encode<'q, DB> + Type<DB>,
+    for<'q> f64: Encode<'q, DB> + Type<DB>,
+    for<'q> bool: Encode<'q, DB> + Type<DB>,
+    for<'q> Vec<u8>: Encode<'q, DB> + Type<DB>,
+    for<'q> Date: Encode<'q, DB> + Type<DB>,
+    for<'q> Time: Encode<'q, DB> + Type<DB>,
+    for<'q> PrimitiveDateTime: Encode<'q, DB> + Type<DB>,
+    for<'q> OffsetDateTime: Encode<'q, DB> + Type<DB>,
+    usize: ColumnIndex<DB::Row>,
+{
+    let rows = bind_values(sqlx::query(&DB::statement(query, &values)), values)?
+        .fetch_all(&mut *conn)
+        .await?;
+
+    // the statement is described for the nullability of its columns, which
+    // rows do not carry. With Postgres, this caches the statement with the
+    // parameter types the database infers, under a text only the calls
+    // without values share, see `BindExact::statement`
+    let describe = conn.describe(query).await?;
+    // the names and types are read from the first row if there is one, as
+    // they are those of the values rather than the ones the database
+    // infers, e.g. with the dynamic typing of SQLite
+    let columns = rows
+        .first()
+        .map_or(describe.columns(), |row| row.columns())
+        .iter()
+        .enumerate()
+        .map(|(i, column)| ColumnInfo {
+            name: column.name().to_string(),
+            type_name: column.type_info().name().to_string(),
+            nullable: describe.nullable(i),
+        })
+        .collect();
+
+    let rows = rows
+        .iter()
+        .map(|row| {
+            (0..row.len())
+                .map(|i| to_json(row.try_get_raw(i)?, decode))
+                .collect::<Result<Vec<_>>>()
+        })
+        .collect::<Result<_>>()?;
+
+    Ok(ColumnarRows { columns, rows })
+}
+
+/// Reads `rows` as they arrive from the database and sends them on in
+/// chunks of `chunk_size`, until the rows run out, an error occurs or the
+/// receiving [`Cursor`] is dropped.
+async fn stream_rows<DB: Database>(
+    rows: Result<BoxStream<'_, std::result::Result<DB::Row, sqlx::Error>>>,
+    to_json: for<'r> fn(DB::ValueRef<'r>, DecodeOptions) -> Result<JsonValue>,
+    decode: DecodeOptions,
+    chunk_size: usize,
+    sender: mpsc::Sender<Result<Vec<HashMap<String, JsonValue>>>>,
+) where
+    usize: ColumnIndex<DB::Row>,
+{
+    // binding the values may already have failed
+    let mut rows = match rows {
+        Ok(rows) => rows,
+        Err(e) => {
+            let _ = sender.send(Err(e)).await;
+            return;
+        }
+    };
+
+    let mut chunk = Vec::with_capacity(chunk_size);
+    loop {
+        match poll_fn(|cx| rows.as_mut().poll_next(cx)).await {
+            Some(Ok(row)) => match row_to_json::<DB>(&row, to_json, decode) {
+                Ok(value) => chunk.push(value),
+                Err(e) => {
+                    let _ = sender.send(Err(e)).await;
+                    return;
+                }
+            },
+            Some(Err(e)) => {
+                let _ = sender.send(Err(e.into())).await;
+                return;
+            }
+            None => break,
+        }
+
+        if chunk.len() >= chunk_size && sender.send(Ok(std::mem::take(&mut chunk))).await.is_err() {
+            return;
+        }
+    }
+
+    if !chunk.is_empty() {
+        let _ = sender.send(Ok(chunk)).await;
+    }
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use super::*;
+    use sqlx::ConnectOptions;
+
+    fn app_dir() -> PathBuf {
+        ["app", "data"].iter().collect()
+    }
+
+    fn in_app_dir(path: &str) -> String {
+        format!("sqlite:{}", app_dir().join(path).display())
+    }
+
+    #[test]
+    fn path_mapper_joins_relative_paths() {
+        assert_eq!(
+            path_mapper(app_dir(), "sqlite:test.db", false).unwrap(),
+            in_app_dir("test.db")
+        );
+        assert_eq!(
+            path_mapper(app_dir(), "sqlite:dbs/test.db", false).unwrap(),
+            in_app_dir("dbs/test.db")
+        );
+    }
+
+    #[test]
+    fn path_mapper_keeps_query_string() {
+        assert_eq!(
+            path_mapper(app_dir(), "sqlite:test.db?mode=ro", false).unwrap(),
+            in_app_dir("test.db?mode=ro")
+        );
+    }
+
+    #[test]
+    fn path_mapper_rejects_parent_dirs() {
+        assert!(path_mapper(app_dir(), "sqlite:../test.db", false).is_err());
+        assert!(path_mapper(app_dir(), "sqlite:dbs/../../test.db", false).is_err());
+        assert_eq!(
+            path_mapper(app_dir(), "sqlite:../test.db", true).unwrap(),
+            in_app_dir("../test.db")
+        );
+    }
+
+    #[test]
+    fn path_mapper_rejects_absolute_paths() {
+        assert!(path_mapper(app_dir(), "sqlite:/tmp/test.db", false).is_err());
+        if cfg!(unix) {
+            assert_eq!(
+                path_mapper(app_dir(), "sqlite:/tmp/test.db", true).unwrap(),
+                "sqlite:/tmp/test.db"
+            );
+        }
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn path_mapper_rejects_windows_prefixes() {
+        assert!(path_mapper(app_dir(), r"sqlite:C:\data\test.db", false).is_err());
+        assert!(path_mapper(app_dir(), r"sqlite:\\server\share\test.db", false).is_err());
+        assert_eq!(
+            path_mapper(app_dir(), r"sqlite:C:\data\test.db", true).unwrap(),
+            r"sqlite:C:\data\test.db"
+        );
+    }
+
+    fn options(value: JsonValue) -> ConnectionOptions {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn pragma_value_accepts_numbers_and_words() {
+        assert_eq!(
+            pragma_value("cache_size", &(-8000).into()).unwrap(),
+            "-8000"
+        );
+        assert_eq!(
+            pragma_value("recursive_triggers", &true.into()).unwrap(),
+            "true"
+        );
+        assert_eq!(
+            pragma_value("temp_store", &"memory".into()).unwrap(),
+            "memory"
+        );
+        assert_eq!(pragma_value("mmap_size", &"1e6".into()).unwrap(), "1e6");
+    }
+
+    #[test]
+    fn pragma_value_rejects_unquoted_sql() {
+        for value in [
+            "'memory'",
+            "\"memory\"",
+            "1; DROP TABLE t",
+            "fast mode",
+            "a\tb",
+            "",
+        ] {
+            assert!(
+                pragma_value("temp_store", &value.into()).is_err(),
+                "{value}"
+            );
+        }
+        assert!(pragma_value("temp_store", &JsonValue::Null).is_err());
+        assert!(pragma_value("temp_store", &serde_json::json!(["memory"])).is_err());
+        for key in ["cache size", "cache_size;", "'cache_size'", "1cache", ""] {
+            assert!(pragma_value(key, &1.into()).is_err(), "{key}");
+        }
+        assert!(pragma_value("KEY", &"secret".into()).is_err());
+        assert!(pragma_value("rekey", &"secret".into()).is_err());
+    }
+
+    #[test]
+    fn frontend_options_fill_unset_config_fields() {
+        let config = options(serde_json::json!({
+            "maxConnections": 5,
+            "sqlite": { "journalMode": "wal", "pragmas": { "cache_size": -8000 } },
+        }));
+        let merged = options(serde_json::json!({
+            "maxConnections": 1,
+            "idleTimeout": 60,
+            "sqlite": { "busyTimeout": 5.0, "baseDir": "appData" },
+        }))
+        .or(Some(&config));
+
+        assert_eq!(merged.max_connections, Some(1));
+        assert_eq!(merged.idle_timeout, Some(60));
+        assert_eq!(merged.read_only, None);
+        let sqlite = merged.sqlite.unwrap();
+        assert_eq!(sqlite.journal_mode.as_deref(), Some("wal"));
+        assert_eq!(sqlite.busy_timeout, Some(5.0));
+        assert!(matches!(sqlite.base_dir, Some(BaseDir::AppData)));
+        assert_eq!(sqlite.pragmas["cache_size"], -8000);
+    }
+
+    #[test]
+    fn config_read_only_cannot_be_lifted() {
+        let config = options(serde_json::json!({ "readOnly": true }));
+        let merged = options(serde_json::json!({ "readOnly": false })).or(Some(&config));
+        assert!(merged.is_read_only());
+        let merged = ConnectionOptions::default().or(Some(&config));
+        assert!(merged.is_read_only());
+
+        let config = options(serde_json::json!({ "readOnly": false }));
+        let merged = options(serde_json::json!({ "readOnly": true })).or(Some(&config));
+        assert!(merged.is_read_only());
+    }
+
+    #[test]
+    fn database_file_resolves_equivalent_paths() {
+        let file = database_file(app_dir(), "sqlite:test.db", false).unwrap();
+        assert_eq!(file, app_dir().join("test.db"));
+        for url in [
+            "sqlite:./test.db",
+            "sqlite:test.db?mode=rwc",
+            "sqlite:dbs/../test.db",
+            "sqlite:./dbs/.././test.db",
+        ] {
+            assert_eq!(database_file(app_dir(), url, true).unwrap(), file, "{url}");
+        }
+        assert_ne!(
+            database_file(app_dir(), "sqlite:dbs/test.db", false).unwrap(),
+            file
+        );
+        assert!(database_file(app_dir(), "sqlite:dbs/../test.db", false).is_err());
+    }
+
+    #[test]
+    fn select_columns_describes_nullability_of_rows() {
+        tauri::async_runtime::block_on(async {
+            let mut conn = SqliteConnectOptions::from_str("sqlite::memory:")
+                .unwrap()
+                .connect()
+                .await
+                .unwrap();
+            conn.execute("CREATE TABLE t (id INTEGER NOT NULL, name TEXT)")
+                .await
+                .unwrap();
+            conn.execute("INSERT INTO t VALUES (1, NULL)")
+                .await
+                .unwrap();
+
+            let result = select_columns::<Sqlite>(
+                &mut conn,
+                "SELECT id, name FROM t",
+                Vec::new(),
+                crate::decode::sqlite::to_json,
+                DecodeOptions::default(),
+            )
+            .await
+            .unwrap();
+            let nullable: Vec<_> = result.columns.iter().map(|c| c.nullable).collect();
+            assert_eq!(nullable, [Some(false), Some(true)]);
+            assert_eq!(result.rows, [[JsonValue::from(1), JsonValue::Null]]);
+        });
+    }
+
+    #[cfg(feature = "sqlcipher")]
+    #[test]
+    fn rekey_wal_database() {
+        tauri::async_runtime::block_on(async {
+            let dir = std::env::temp_dir().join(format!("tauri-sql-rekey-{}", std::process::id()));
+            create_dir_all(&dir).unwrap();
+            let options = |key| {
+                SqliteConnectOptions::new()
+                    .filename(dir.join("test.db"))
+                    .create_if_missing(true)
+                    .journal_mode(SqliteJournalMode::Wal)
+                    .pragma("key", quote_key(key))
+            };
+            let count = |pool| async move {
+                sqlx::query_scalar::<_, i64>("SELECT count(*) FROM t")
+                    .fetch_one(&pool)
+                    .await
+            };
+
+            let pool = PoolOptions::<Sqlite>::new()
+                .connect_with(options("old"))
+                .await
+                .unwrap();
+            sqlx::query("CREATE TABLE t (x)")
+                .execute(&pool)
+                .await
+                .unwrap();
+            sqlx::query("INSERT INTO t VALUES (1)")
+                .execute(&pool)
+                .await
+                .unwrap();
+            // an idle connection that still uses the old key
+            drop(pool.acquire().await.unwrap());
+
+            let rekeyed = rekey_sqlite(&pool, "new").await.unwrap();
+            pool.close().await;
+            assert_eq!(count(rekeyed.clone()).await.unwrap(), 1);
+            rekeyed.close().await;
+
+            let pool = PoolOptions::<Sqlite>::new()
+                .connect_with(options("new"))
+                .await
+                .unwrap();
+            assert_eq!(count(pool.clone()).await.unwrap(), 1);
+            pool.close().await;
+            let pool = PoolOptions::<Sqlite>::new().connect_lazy_with(options("old"));
+            assert!(count(pool).await.is_err());
+
+            std::fs::remove_dir_all(dir).unwrap();
+        });
+    }
+}